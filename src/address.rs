@@ -0,0 +1,73 @@
+#[derive(Debug, PartialEq)]
+pub(crate) struct Address {
+  pub(crate) host: String,
+  pub(crate) path: String,
+}
+
+impl Address {
+  pub(crate) fn parse(url: &str) -> Address {
+    let (host, path) = if let Some(i) = url.find("://") {
+      let rest = &url[i + 3..];
+      let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+      let host = authority.rsplit('@').next().unwrap_or(authority);
+      (host.split(':').next().unwrap_or(host), path)
+    } else {
+      match url.find(':') {
+        Some(colon) if !url[..colon].contains('/') => {
+          let host = &url[..colon];
+          (host.rsplit('@').next().unwrap_or(host), &url[colon + 1..])
+        }
+        _ => ("", url),
+      }
+    };
+
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    Address {
+      host: host.to_lowercase(),
+      path: path.trim_end_matches('/').to_owned(),
+    }
+  }
+
+  pub(crate) fn project(&self) -> &str {
+    self.path.rsplit('/').next().unwrap_or(&self.path)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn case(url: &str, host: &str, path: &str) {
+    assert_eq!(
+      Address::parse(url),
+      Address {
+        host: host.to_owned(),
+        path: path.to_owned(),
+      },
+      "{}",
+      url
+    );
+  }
+
+  #[test]
+  fn parse() {
+    case("https://github.com/casey/just", "github.com", "casey/just");
+    case(
+      "https://github.com/casey/just.git",
+      "github.com",
+      "casey/just",
+    );
+    case("https://GitHub.com/casey/just/", "github.com", "casey/just");
+    case("git@github.com:casey/just.git", "github.com", "casey/just");
+    case(
+      "ssh://git@example.com:2222/casey/just.git",
+      "example.com",
+      "casey/just",
+    );
+    case("github.com:casey/just", "github.com", "casey/just");
+    case("/srv/git/casey/just.git", "", "srv/git/casey/just");
+    case("file:///srv/git/casey/just.git", "", "srv/git/casey/just");
+  }
+}
@@ -0,0 +1,10 @@
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Default, Deserialize)]
+pub(crate) enum Backend {
+  #[default]
+  #[serde(rename = "git2")]
+  Git2,
+  #[serde(rename = "cli")]
+  Cli,
+}
@@ -0,0 +1,122 @@
+use crate::common::*;
+
+pub(crate) struct Branch {
+  pub(crate) name: String,
+  pub(crate) upstream: Option<String>,
+  pub(crate) ahead: usize,
+  pub(crate) behind: usize,
+}
+
+impl Branch {
+  pub(crate) fn load(repo: &git2::Repository) -> Result<Vec<Branch>, git2::Error> {
+    let mut branches = Vec::new();
+
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+      let (branch, _) = branch?;
+
+      let name = String::from_utf8_lossy(branch.name_bytes()?).into_owned();
+
+      let upstream = match branch.upstream() {
+        Ok(upstream) => upstream,
+        Err(error) if error.code() == git2::ErrorCode::NotFound => {
+          branches.push(Branch {
+            name,
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+          });
+          continue;
+        }
+        Err(error) => return Err(error),
+      };
+
+      let (ahead, behind) = match (branch.get().target(), upstream.get().target()) {
+        (Some(local), Some(upstream)) => repo.graph_ahead_behind(local, upstream)?,
+        _ => (0, 0),
+      };
+
+      branches.push(Branch {
+        name,
+        upstream: Some(String::from_utf8_lossy(upstream.name_bytes()?).into_owned()),
+        ahead,
+        behind,
+      });
+    }
+
+    Ok(branches)
+  }
+
+  pub(crate) fn is_unpushed(&self) -> bool {
+    self.upstream.is_none() || self.ahead > 0
+  }
+
+  pub(crate) fn is_synced(&self) -> bool {
+    self.upstream.is_some() && self.ahead == 0 && self.behind == 0
+  }
+
+  pub(crate) fn report(&self) -> report::Branch {
+    report::Branch {
+      name: self.name.clone(),
+      upstream: self.upstream.clone(),
+      ahead: self.ahead,
+      behind: self.behind,
+    }
+  }
+}
+
+impl Display for Branch {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}: ", self.name)?;
+
+    match &self.upstream {
+      None => write!(f, "no upstream"),
+      Some(upstream) => match (self.ahead, self.behind) {
+        (0, 0) => write!(f, "up to date with {}", upstream),
+        (ahead, 0) => write!(f, "{} ahead of {}", ahead, upstream),
+        (0, behind) => write!(f, "{} behind {}", behind, upstream),
+        (ahead, behind) => write!(f, "{} ahead, {} behind {}", ahead, behind, upstream),
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::testing::git;
+
+  #[test]
+  fn ahead_and_no_upstream() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let origin = tempdir.path().join("origin");
+    let clone = tempdir.path().join("clone");
+    let origin = origin.to_str().unwrap();
+    let clone = clone.to_str().unwrap();
+
+    git(&["init", origin])?;
+    git(&["-C", origin, "commit", "--allow-empty", "-m", "one"])?;
+    git(&["clone", origin, clone])?;
+    git(&["-C", clone, "commit", "--allow-empty", "-m", "two"])?;
+    git(&["-C", clone, "branch", "local"])?;
+
+    let repo = git2::Repository::open(clone).context(error::Git { path: clone })?;
+
+    let mut branches = Branch::load(&repo).context(error::Git { path: clone })?;
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(branches.len(), 2);
+
+    assert_eq!(branches[0].name, "local");
+    assert_eq!(branches[0].upstream, None);
+    assert!(branches[0].is_unpushed());
+
+    assert!(branches[1].upstream.is_some());
+    assert_eq!(branches[1].ahead, 1);
+    assert_eq!(branches[1].behind, 0);
+    assert!(branches[1].is_unpushed());
+
+    Ok(())
+  }
+}
@@ -0,0 +1,105 @@
+use crate::common::*;
+
+#[derive(StructOpt, Deserialize, Default, Clone)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub(crate) struct CloneOptions {
+  #[structopt(long = "depth")]
+  depth: Option<usize>,
+  #[structopt(long = "branch")]
+  branch: Option<String>,
+  #[structopt(long = "recurse-submodules")]
+  #[serde(default, rename = "recurse-submodules")]
+  recurse_submodules: bool,
+  #[structopt(long = "bare")]
+  #[serde(default)]
+  bare: bool,
+  #[structopt(long = "filter")]
+  filter: Option<String>,
+}
+
+impl CloneOptions {
+  /// Fill in options not given on the command line from `defaults`.
+  pub(crate) fn or(self, defaults: &CloneOptions) -> CloneOptions {
+    CloneOptions {
+      depth: self.depth.or(defaults.depth),
+      branch: self.branch.or_else(|| defaults.branch.clone()),
+      recurse_submodules: self.recurse_submodules || defaults.recurse_submodules,
+      bare: self.bare || defaults.bare,
+      filter: self.filter.or_else(|| defaults.filter.clone()),
+    }
+  }
+
+  /// Whether these options can only be honored by the `git` command line
+  /// client, since libgit2 supports neither shallow nor partial clones.
+  pub(crate) fn requires_cli(&self) -> bool {
+    self.depth.is_some() || self.filter.is_some()
+  }
+
+  pub(crate) fn configure(&self, builder: &mut git2::build::RepoBuilder) {
+    builder.bare(self.bare);
+
+    if let Some(branch) = &self.branch {
+      builder.branch(branch);
+    }
+  }
+
+  pub(crate) fn recurse_submodules(&self) -> bool {
+    self.recurse_submodules
+  }
+
+  pub(crate) fn args(&self) -> Vec<OsString> {
+    let mut args: Vec<OsString> = Vec::new();
+
+    if let Some(depth) = self.depth {
+      args.push(format!("--depth={}", depth).into());
+    }
+
+    if let Some(branch) = &self.branch {
+      args.push("--branch".into());
+      args.push(branch.into());
+    }
+
+    if self.recurse_submodules {
+      args.push("--recurse-submodules".into());
+    }
+
+    if self.bare {
+      args.push("--bare".into());
+    }
+
+    if let Some(filter) = &self.filter {
+      args.push(format!("--filter={}", filter).into());
+    }
+
+    args
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn or() {
+    let defaults: CloneOptions =
+      serde_yaml::from_str("{depth: 1, filter: 'blob:none', recurse-submodules: true}").unwrap();
+
+    let options = CloneOptions {
+      depth: Some(10),
+      branch: Some("dev".to_owned()),
+      ..CloneOptions::default()
+    }
+    .or(&defaults);
+
+    assert_eq!(
+      options.args(),
+      &[
+        "--depth=10",
+        "--branch",
+        "dev",
+        "--recurse-submodules",
+        "--filter=blob:none"
+      ]
+    );
+  }
+}
@@ -4,26 +4,36 @@ pub(crate) use std::{
   env,
   ffi::OsString,
   fmt::{self, Display, Formatter},
-  fs, io,
+  fs,
+  io::{self, BufRead, BufReader},
   path::{Path, PathBuf},
-  process::{self, Command, ExitStatus},
+  process::{self, Command, ExitStatus, Stdio},
   rc::Rc,
+  str::FromStr,
+  sync::atomic::{AtomicBool, Ordering},
+  thread,
 };
 
 // dependencies
-pub(crate) use indicatif::{ProgressBar, ProgressStyle};
+pub(crate) use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+pub(crate) use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
 pub(crate) use libc::EXIT_FAILURE;
-pub(crate) use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-pub(crate) use serde::Deserialize;
+pub(crate) use rayon::iter::{
+  IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+pub(crate) use serde::{Deserialize, Serialize};
 pub(crate) use snafu::{ResultExt, Snafu};
 pub(crate) use structopt::StructOpt;
 pub(crate) use tera::Tera;
 
 // modules
-pub(crate) use crate::{error, raw};
+pub(crate) use crate::{error, manifest, raw, report};
 
 // structs and enums
 pub(crate) use crate::{
-  color::Color, config::Config, error::Error, opt::Opt, provider::Provider, repo::Repo, spec::Spec,
-  src::Src, status::Status,
+  address::Address, backend::Backend, branch::Branch, clone_options::CloneOptions, color::Color,
+  config::Config, credentials::Credentials, error::Error, format::Format, group::Group, link::Link,
+  manifest::Manifest, opt::Opt, pattern::Pattern, provider::Provider, pull::Pull, push::Push,
+  ref_status::RefStatus, ref_update::RefUpdate, repo::Repo, select::Select, spec::Spec, src::Src,
+  status::Status,
 };
@@ -2,16 +2,33 @@ use crate::common::*;
 
 const FILENAME: &str = "config.yaml";
 
+const MAX_DEPTH: usize = 1;
+
+#[cfg(target_os = "macos")]
+const OPENER: &str = "open";
+
+#[cfg(not(target_os = "macos"))]
+const OPENER: &str = "xdg-open";
+
 const DEFAULT: &str = "\
 default-provider: github
 srcdir:           ~/src
 tmpdir:           ~/tmp
+max-depth:        1
 
 providers:
   github:
-    remote-template: git@github.com:{{user}}/{{project}}.git
+    default-template: ssh
+    templates:
+      ssh:   git@github.com:{{user}}/{{project}}.git
+      https: https://github.com/{{user}}/{{project}}.git
+    web-template: 'https://github.com/{{user}}/{{project}}{% if file %}/blob/{{commit}}/{{file}}{% if line %}#L{{line}}{% endif %}{% endif %}'
   bitbucket:
-    remote-template: git@bitbucket.org:{{user}}/{{project}}.git
+    default-template: ssh
+    templates:
+      ssh:   git@bitbucket.org:{{user}}/{{project}}.git
+      https: https://bitbucket.org/{{user}}/{{project}}.git
+    web-template: 'https://bitbucket.org/{{user}}/{{project}}{% if file %}/src/{{commit}}/{{file}}{% if line %}#lines-{{line}}{% endif %}{% endif %}'
 ";
 
 pub(crate) struct Config {
@@ -19,7 +36,12 @@ pub(crate) struct Config {
   default_user: String,
   srcdir: PathBuf,
   tmpdir: PathBuf,
+  max_depth: usize,
+  jobs: usize,
   providers: BTreeMap<String, Rc<Provider>>,
+  groups: BTreeMap<String, Group>,
+  opener: String,
+  clone_backend: Backend,
 }
 
 impl Config {
@@ -33,7 +55,7 @@ impl Config {
 
   pub(crate) fn load() -> Result<Config, Error> {
     let path = xdg::BaseDirectories::with_prefix("src")
-      .context(error::BaseDirectoriesError)?
+      .context(error::BaseDirectories)?
       .find_config_file(Self::filename());
 
     let raw = if let Some(path) = path {
@@ -49,13 +71,37 @@ impl Config {
     Self::from_raw(raw)
   }
 
-  fn from_raw(raw: raw::Config) -> Result<Config, Error> {
+  pub(crate) fn from_raw(raw: raw::Config) -> Result<Config, Error> {
+    // Variables that aren't valid UTF-8 can't be used in templates, so they
+    // are left out rather than converted lossily.
+    let env = env::vars_os()
+      .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+      .collect::<BTreeMap<String, String>>();
+
     let mut providers = BTreeMap::new();
     for (name, provider) in raw.providers {
-      let provider = Rc::new(Provider::from_raw(&name, provider)?);
+      let provider = Rc::new(Provider::from_raw(&name, provider, &raw.variables, &env)?);
       providers.insert(name, provider);
     }
 
+    let srcdir = Config::expand_tilde(raw.srcdir)?;
+
+    let mut groups = BTreeMap::new();
+    for (name, patterns) in raw.groups {
+      let group = Group::from_raw(&name, patterns)?;
+
+      for literal in group.literals() {
+        if !Repo::is_repo(&srcdir.join(literal)) {
+          eprintln!(
+            "warning: group `{}` contains unknown repository `{}`",
+            name, literal
+          );
+        }
+      }
+
+      groups.insert(name, group);
+    }
+
     let default_provider = raw.default_provider;
 
     let default_user = if let Some(default_user) = raw.default_user {
@@ -71,10 +117,15 @@ impl Config {
           name: default_provider.clone(),
         })?
         .clone(),
-      srcdir: Config::expand_tilde(raw.srcdir)?,
+      srcdir,
       tmpdir: Config::expand_tilde(raw.tmpdir)?,
+      max_depth: raw.max_depth.unwrap_or(MAX_DEPTH),
+      jobs: raw.jobs.unwrap_or(0),
       default_user,
       providers,
+      groups,
+      opener: raw.opener.unwrap_or_else(|| OPENER.to_owned()),
+      clone_backend: raw.clone_backend.unwrap_or_default(),
     })
   }
 
@@ -85,7 +136,7 @@ impl Config {
       Ok(
         dirs::home_dir()
           .ok_or_else(|| Error::HomeDirectory)?
-          .join(path.strip_prefix("~").unwrap().to_path_buf()),
+          .join(path.strip_prefix("~").unwrap()),
       )
     } else {
       Ok(path)
@@ -100,53 +151,138 @@ impl Config {
     &self.tmpdir
   }
 
+  pub(crate) fn max_depth(&self) -> usize {
+    self.max_depth
+  }
+
+  pub(crate) fn jobs(&self) -> usize {
+    self.jobs
+  }
+
+  pub(crate) fn opener(&self) -> &str {
+    &self.opener
+  }
+
+  pub(crate) fn clone_backend(&self) -> Backend {
+    self.clone_backend
+  }
+
+  pub(crate) fn group(&self, name: &str) -> Result<&Group, Error> {
+    self.groups.get(name).ok_or_else(|| Error::Group {
+      name: name.to_owned(),
+    })
+  }
+
+  pub(crate) fn identify(&self, url: &str) -> Option<Spec> {
+    self.identify_explicit(url).map(|(spec, _)| spec)
+  }
+
+  /// Identify `url`, also returning whether the spec's user was taken from
+  /// `url` itself, rather than filled in from the default.
+  fn identify_explicit(&self, url: &str) -> Option<(Spec, bool)> {
+    self.providers.values().find_map(|provider| {
+      let (user, project) = provider.parse(url)?;
+      let explicit = user.is_some();
+
+      Some((
+        Spec {
+          user: user.unwrap_or_else(|| self.user(provider).to_owned()),
+          provider: provider.clone(),
+          project,
+        },
+        explicit,
+      ))
+    })
+  }
+
+  /// The user contained in `url`, if it matches a provider whose template
+  /// includes one.
+  pub(crate) fn remote_user(&self, url: &str) -> Option<String> {
+    self
+      .identify_explicit(url)
+      .filter(|(_, explicit)| *explicit)
+      .map(|(spec, _)| spec.user)
+  }
+
+  /// Identify the project that `remotes` belong to, preferring a remote whose
+  /// URL contains a user, and then a remote named after the provider it
+  /// matches. Returns the remote's name and its spec.
+  pub(crate) fn identify_remotes(
+    &self,
+    remotes: &BTreeMap<String, String>,
+  ) -> Option<(String, Spec)> {
+    remotes
+      .iter()
+      .filter_map(|(name, url)| {
+        self
+          .identify_explicit(url)
+          .map(|(spec, explicit)| (name, spec, explicit))
+      })
+      .min_by_key(|(name, spec, explicit)| (!explicit, *name != &spec.provider.name))
+      .map(|(name, spec, _)| (name.clone(), spec))
+  }
+
   fn user<'a>(&'a self, provider: &'a Provider) -> &'a str {
     provider.default_user.as_ref().unwrap_or(&self.default_user)
   }
 
   pub(crate) fn spec(&self, values: Vec<String>) -> Result<Spec, Error> {
     match values.as_slice() {
-      [project] => Ok(Spec {
-        provider: self.default_provider.clone(),
-        user: self.user(&self.default_provider).to_owned(),
-        project: project.clone(),
-      }),
-      [provider, project] => {
-        let provider = self
-          .providers
-          .get(provider)
-          .ok_or_else(|| Error::Provider {
-            name: provider.clone(),
-          })?;
-
-        let user = self.user(provider).to_owned();
-
-        Ok(Spec {
-          project: project.clone(),
-          provider: provider.clone(),
-          user,
-        })
-      }
-      [provider, user, project] => {
-        let provider = self
-          .providers
-          .get(provider)
-          .ok_or_else(|| Error::Provider {
-            name: provider.clone(),
-          })?;
-
-        Ok(Spec {
-          project: project.clone(),
-          provider: provider.clone(),
-          user: user.clone(),
-        })
-      }
+      [value] => self.parse(value),
+      [provider, project] => self.resolve(Some(provider), None, project),
+      [provider, user, project] => self.resolve(Some(provider), Some(user), project),
       _ => Err(Error::internal(format!(
         "incorrect number of values for spec: {:?}",
         values
       ))),
     }
   }
+
+  fn parse(&self, value: &str) -> Result<Spec, Error> {
+    if let Some((provider, path)) = value.split_once(':') {
+      if self.providers.contains_key(provider) {
+        return self.parse_path(Some(provider), path);
+      }
+    }
+
+    if value.contains(':') || value.starts_with('/') {
+      return self.identify(value).ok_or_else(|| Error::UnknownRemote {
+        url: value.to_owned(),
+      });
+    }
+
+    self.parse_path(None, value)
+  }
+
+  fn parse_path(&self, provider: Option<&str>, path: &str) -> Result<Spec, Error> {
+    match path.split_once('/') {
+      Some((user, project)) => self.resolve(provider, Some(user), project),
+      None => self.resolve(provider, None, path),
+    }
+  }
+
+  pub(crate) fn resolve(
+    &self,
+    provider: Option<&str>,
+    user: Option<&str>,
+    project: &str,
+  ) -> Result<Spec, Error> {
+    let provider = match provider {
+      Some(provider) => self
+        .providers
+        .get(provider)
+        .ok_or_else(|| Error::Provider {
+          name: provider.to_owned(),
+        })?,
+      None => &self.default_provider,
+    };
+
+    Ok(Spec {
+      user: user.unwrap_or_else(|| self.user(provider)).to_owned(),
+      provider: provider.clone(),
+      project: project.to_owned(),
+    })
+  }
 }
 
 #[cfg(test)]
@@ -165,4 +301,42 @@ mod tests {
   fn default() {
     serde_yaml::from_str::<raw::Config>(DEFAULT).unwrap();
   }
+
+  #[test]
+  fn spec() {
+    let config =
+      Config::from_raw(serde_yaml::from_str(&format!("default-user: me\n{}", DEFAULT)).unwrap())
+        .unwrap();
+
+    let case = |value: &str, provider: &str, user: &str, project: &str| {
+      let spec = config.spec(vec![value.to_owned()]).unwrap();
+      assert_eq!(
+        (
+          spec.provider.name.as_str(),
+          spec.user.as_str(),
+          spec.project.as_str()
+        ),
+        (provider, user, project),
+        "{}",
+        value
+      );
+    };
+
+    case("just", "github", "me", "just");
+    case("casey/just", "github", "casey", "just");
+    case("bitbucket:just", "bitbucket", "me", "just");
+    case("bitbucket:casey/just", "bitbucket", "casey", "just");
+    case("https://github.com/casey/just", "github", "casey", "just");
+    case("git@github.com:casey/just.git", "github", "casey", "just");
+    case(
+      "https://bitbucket.org/casey/just.git",
+      "bitbucket",
+      "casey",
+      "just",
+    );
+
+    assert!(config
+      .spec(vec!["https://gitlab.com/casey/just".to_owned()])
+      .is_err());
+  }
 }
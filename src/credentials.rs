@@ -0,0 +1,46 @@
+/// Supplies credentials to libgit2, trying each source at most once so that a
+/// rejected credential fails the operation instead of being retried forever.
+#[derive(Default)]
+pub(crate) struct Credentials {
+  agent: bool,
+  helper: bool,
+  default: bool,
+}
+
+impl Credentials {
+  pub(crate) fn get(
+    &mut self,
+    url: &str,
+    username: Option<&str>,
+    allowed: git2::CredentialType,
+  ) -> Result<git2::Cred, git2::Error> {
+    use git2::CredentialType;
+
+    let username = username.unwrap_or("git");
+
+    if allowed.contains(CredentialType::USERNAME) {
+      return git2::Cred::username(username);
+    }
+
+    if allowed.contains(CredentialType::SSH_KEY) && !self.agent {
+      self.agent = true;
+      return git2::Cred::ssh_key_from_agent(username);
+    }
+
+    if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && !self.helper {
+      self.helper = true;
+      let config = git2::Config::open_default()?;
+      return git2::Cred::credential_helper(&config, url, Some(username));
+    }
+
+    if allowed.contains(CredentialType::DEFAULT) && !self.default {
+      self.default = true;
+      return git2::Cred::default();
+    }
+
+    Err(git2::Error::from_str(&format!(
+      "no credentials available for `{}`",
+      url
+    )))
+  }
+}
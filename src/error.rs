@@ -1,10 +1,14 @@
 use crate::common::*;
 
+const EXIT_PARTIAL: i32 = 2;
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
 pub(crate) enum Error {
   #[snafu(display("Git failure at `{}`: {}", path.display(), source))]
   Git { source: git2::Error, path: PathBuf },
+  #[snafu(display("Failed to clone `{}`: {}", url, source))]
+  Clone { source: git2::Error, url: String },
   #[snafu(display("I/O failure at `{}`: {}", path.display(), source))]
   Io { source: io::Error, path: PathBuf },
   #[snafu(display("Deserialization of text at `{}` failed: {}", path.display(), source))]
@@ -13,13 +17,52 @@ pub(crate) enum Error {
     path: PathBuf,
   },
   #[snafu(display("Failed to access XDG base directories: {}", source))]
-  BaseDirectoriesError { source: xdg::BaseDirectoriesError },
+  BaseDirectories { source: xdg::BaseDirectoriesError },
   #[snafu(display("Could not determine home directory"))]
   HomeDirectory,
   #[snafu(display("Unknown default provider: {}", name))]
   DefaultProvider { name: String },
   #[snafu(display("Unknown provider: {}", name))]
   Provider { name: String },
+  #[snafu(display("Unknown group: {}", name))]
+  Group { name: String },
+  #[snafu(display("Invalid pattern in group `{}`: {}", group, source))]
+  GroupPattern {
+    group: String,
+    source: globset::Error,
+  },
+  #[snafu(display("No repository named `{}`", name))]
+  UnknownRepository { name: String },
+  #[snafu(display("No provider matches remote: {}", url))]
+  UnknownRemote { url: String },
+  #[snafu(display("Unknown protocol for provider `{}`: {}", provider, protocol))]
+  Protocol { provider: String, protocol: String },
+  #[snafu(display(
+    "Provider `{}` has multiple templates but no `default-template`",
+    provider
+  ))]
+  DefaultTemplate { provider: String },
+  #[snafu(display(
+    "Provider `{}` defines template `{}` in both `remote-template` and `templates`",
+    provider,
+    template
+  ))]
+  DuplicateTemplate { provider: String, template: String },
+  #[snafu(display(
+    "Template `{}` of provider `{}` failed to render: {}",
+    template,
+    provider,
+    message
+  ))]
+  Variable {
+    provider: String,
+    template: String,
+    message: String,
+  },
+  #[snafu(display("Provider `{}` has no `web-template`", provider))]
+  WebTemplate { provider: String },
+  #[snafu(display("No remote of `{}` matches a provider", path.display()))]
+  UnidentifiedRepository { path: PathBuf },
   #[snafu(display("Failed to parse template for `{}`: {}", name, source))]
   Template { name: String, source: tera::Error },
   #[snafu(display("Failed to render template for `{}`: {}", provider, source))]
@@ -33,7 +76,7 @@ pub(crate) enum Error {
   User { source: env::VarError },
   #[snafu(display(
     "Command `{}` invocation failed: {}",
-    command.into_iter().map(|os_string| {
+    command.iter().map(|os_string| {
       os_string.to_string_lossy().into_owned()
     }).collect::<Vec<String>>().join(" "),
     source
@@ -44,7 +87,7 @@ pub(crate) enum Error {
   },
   #[snafu(display(
     "Command `{}` failed: {}",
-    command.into_iter().map(|os_string| {
+    command.iter().map(|os_string| {
       os_string.to_string_lossy().into_owned()
     }).collect::<Vec<String>>().join(" "),
     status
@@ -55,7 +98,7 @@ pub(crate) enum Error {
   },
   #[snafu(display(
     "Command `{}` failed: {}\n{}\n{}",
-    command.into_iter().map(|os_string| {
+    command.iter().map(|os_string| {
       os_string.to_string_lossy().into_owned()
     }).collect::<Vec<String>>().join(" "),
     status,
@@ -81,11 +124,67 @@ pub(crate) enum Error {
   PushDirty,
   #[snafu(display("Failed to push all repositories to `{}`", remote))]
   PushAll { remote: String },
+  #[snafu(display(
+    "Could not inspect {} {}",
+    count,
+    if *count == 1 { "repository" } else { "repositories" }
+  ))]
+  Inspect { count: usize },
+  #[snafu(display("Failed to serialize JSON: {}", source))]
+  Json { source: serde_json::Error },
+  #[snafu(display("Failed to build thread pool: {}", source))]
+  ThreadPool { source: rayon::ThreadPoolBuildError },
+  #[snafu(display("Failed to fetch all repositories"))]
+  FetchAll,
+  #[snafu(display("Failed to pull all repositories"))]
+  PullAll,
+  #[snafu(display(
+    "Command failed in {} {}",
+    count,
+    if *count == 1 { "repository" } else { "repositories" }
+  ))]
+  RunAll { count: usize },
+  #[snafu(display("Failed to run command in `{}`: {}", name, source))]
+  Run { name: String, source: Box<Error> },
+  #[snafu(display("No manifest file found.\n(Use the `--manifest` flag to specify one.)"))]
+  ManifestMissing,
+  #[snafu(display(
+    "Manifest entries in `{}` must have exactly one of `project` or `url`",
+    path.display()
+  ))]
+  ManifestEntry { path: PathBuf },
+  #[snafu(display("Failed to serialize YAML: {}", source))]
+  Yaml { source: serde_yaml::Error },
+  #[snafu(display(
+    "Failed to sync {} manifest {}",
+    count,
+    if *count == 1 { "entry" } else { "entries" }
+  ))]
+  SyncAll { count: usize },
+  #[snafu(display(
+    "Found {} mismatched {}\n(Use the `--fix` flag to update them.)",
+    count,
+    if *count == 1 { "remote" } else { "remotes" }
+  ))]
+  MirrorMismatch { count: usize },
+  #[snafu(display(
+    "Failed to mirror {} {}",
+    count,
+    if *count == 1 { "repository" } else { "repositories" }
+  ))]
+  MirrorAll { count: usize },
   #[snafu(display("Destination already exists: {}", destination.display()))]
   DestinationExists { destination: PathBuf },
 }
 
 impl Error {
+  pub(crate) fn code(&self) -> i32 {
+    match self {
+      Error::RunAll { .. } => EXIT_PARTIAL,
+      _ => EXIT_FAILURE,
+    }
+  }
+
   pub(crate) fn internal(message: impl Display) -> Error {
     Error::Internal {
       message: message.to_string(),
@@ -0,0 +1,24 @@
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Format {
+  Human,
+  Json,
+  Porcelain,
+}
+
+impl FromStr for Format {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Format, String> {
+    match text {
+      "human" => Ok(Format::Human),
+      "json" => Ok(Format::Json),
+      "porcelain" => Ok(Format::Porcelain),
+      _ => Err(format!(
+        "unknown format `{}`, expected `human`, `json`, or `porcelain`",
+        text
+      )),
+    }
+  }
+}
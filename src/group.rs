@@ -0,0 +1,37 @@
+use crate::common::*;
+
+pub(crate) struct Group {
+  patterns: Vec<Glob>,
+  set: GlobSet,
+}
+
+impl Group {
+  pub(crate) fn from_raw(name: &str, raw: Vec<String>) -> Result<Group, Error> {
+    let mut patterns = Vec::new();
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in raw {
+      let glob = Glob::new(&pattern).context(error::GroupPattern { group: name })?;
+      builder.add(glob.clone());
+      patterns.push(glob);
+    }
+
+    let set = builder
+      .build()
+      .context(error::GroupPattern { group: name })?;
+
+    Ok(Group { patterns, set })
+  }
+
+  pub(crate) fn literals(&self) -> impl Iterator<Item = &str> {
+    self
+      .patterns
+      .iter()
+      .map(Glob::glob)
+      .filter(|pattern| !pattern.contains(&['*', '?', '[', '{'][..]))
+  }
+
+  pub(crate) fn contains(&self, repo: &Repo) -> bool {
+    self.set.is_match(repo.name())
+  }
+}
@@ -0,0 +1,111 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+pub(crate) struct Link {
+  #[structopt(max_values = 3)]
+  target: Vec<String>,
+  #[structopt(long = "file")]
+  file: Option<PathBuf>,
+  #[structopt(long = "line", requires = "file")]
+  line: Option<usize>,
+}
+
+const HEAD: &str = "HEAD";
+
+impl Link {
+  pub(crate) fn url(&self, config: &Config) -> Result<String, Error> {
+    let path = match self.target.as_slice() {
+      [] => Some(Path::new(".")),
+      [target] if Path::new(target).exists() => Some(Path::new(target)),
+      _ => None,
+    };
+
+    let mut context = tera::Context::new();
+
+    // Targets without a checkout link to whatever the provider considers HEAD
+    context.insert("branch", HEAD);
+    context.insert("commit", HEAD);
+
+    let spec = match path {
+      Some(path) => {
+        let repo = Repo::discover(path)?;
+
+        context.insert("branch", repo.head());
+
+        if let Some(commit) = repo.commit() {
+          context.insert("commit", commit);
+        }
+
+        if let Some(file) = &self.file {
+          context.insert("file", &Self::relative(file, repo.workdir()));
+        }
+
+        config
+          .identify_remotes(repo.remotes())
+          .map(|(_, spec)| spec)
+          .ok_or_else(|| Error::UnidentifiedRepository {
+            path: repo.workdir().to_owned(),
+          })?
+      }
+      None => {
+        if let Some(file) = &self.file {
+          context.insert("file", &file.to_string_lossy());
+        }
+
+        config.spec(self.target.clone())?
+      }
+    };
+
+    if let Some(line) = self.line {
+      context.insert("line", &line);
+    }
+
+    spec.web(context)
+  }
+
+  fn relative(file: &Path, workdir: &Path) -> String {
+    let relative = file
+      .canonicalize()
+      .ok()
+      .and_then(|file| {
+        let workdir = workdir.canonicalize().ok()?;
+        file.strip_prefix(workdir).ok().map(Path::to_owned)
+      })
+      .unwrap_or_else(|| file.to_owned());
+
+    relative
+      .components()
+      .map(|component| component.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("/")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::testing::config;
+
+  #[test]
+  fn spec_head() {
+    let config = config(
+      "
+default-provider: github
+srcdir: /
+tmpdir: /
+providers:
+  github:
+    remote-template: git@github.com:{{user}}/{{project}}.git
+    web-template: 'https://github.com/{{user}}/{{project}}/tree/{{branch}}/{{commit}}'
+",
+    );
+
+    let link = Link::from_iter(&["url", "casey/just"]);
+
+    assert_eq!(
+      link.url(&config).unwrap(),
+      "https://github.com/casey/just/tree/HEAD/HEAD"
+    );
+  }
+}
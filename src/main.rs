@@ -1,15 +1,34 @@
+mod address;
+mod backend;
+mod branch;
+mod clone_options;
 mod color;
 mod common;
 mod config;
+mod credentials;
 mod error;
+mod format;
+mod group;
+mod link;
+mod manifest;
 mod opt;
+mod pattern;
 mod provider;
+mod pull;
+mod push;
 mod raw;
+mod ref_status;
+mod ref_update;
 mod repo;
+mod report;
+mod select;
 mod spec;
 mod src;
 mod status;
 
+#[cfg(test)]
+mod testing;
+
 use crate::common::*;
 
 fn main() {
@@ -20,12 +39,12 @@ fn main() {
       eprintln!(
         "{} {}",
         color.error().paint("error:"),
-        color.message().wrap(error),
+        color.message().wrap(&error),
       );
     } else {
       eprintln!("error: {}", error);
     }
 
-    process::exit(EXIT_FAILURE);
+    process::exit(error.code());
   }
 }
@@ -0,0 +1,106 @@
+use crate::common::*;
+
+const FILENAME: &str = "manifest.yaml";
+
+pub(crate) const ORIGIN: &str = "origin";
+
+pub(crate) struct Manifest {
+  pub(crate) entries: Vec<Entry>,
+}
+
+pub(crate) struct Entry {
+  pub(crate) name: String,
+  pub(crate) origin: String,
+  pub(crate) url: String,
+  pub(crate) remotes: BTreeMap<String, String>,
+}
+
+impl Manifest {
+  pub(crate) fn load(config: &Config, path: Option<PathBuf>) -> Result<Manifest, Error> {
+    let path = match path {
+      Some(path) => path,
+      None => xdg::BaseDirectories::with_prefix("src")
+        .context(error::BaseDirectories)?
+        .find_config_file(FILENAME)
+        .ok_or(Error::ManifestMissing)?,
+    };
+
+    let path = &path;
+
+    let text = fs::read_to_string(path).context(error::Io { path })?;
+
+    let raw: raw::Manifest = serde_yaml::from_str(&text).context(error::Deserialize { path })?;
+
+    let mut entries = Vec::new();
+
+    for entry in raw.repositories {
+      let (name, origin, url) = match (entry.project, entry.url) {
+        (Some(project), None) => {
+          let spec = config.resolve(entry.provider.as_deref(), entry.user.as_deref(), &project)?;
+          (
+            entry.name.unwrap_or(project),
+            entry.origin.unwrap_or_else(|| spec.provider.name.clone()),
+            spec.remote(None)?,
+          )
+        }
+        (None, Some(url)) => (
+          entry
+            .name
+            .unwrap_or_else(|| Address::parse(&url).project().to_owned()),
+          entry.origin.unwrap_or_else(|| ORIGIN.to_owned()),
+          url,
+        ),
+        _ => return Err(Error::ManifestEntry { path: path.clone() }),
+      };
+
+      entries.push(Entry {
+        name,
+        origin,
+        url,
+        remotes: entry.remotes,
+      });
+    }
+
+    Ok(Manifest { entries })
+  }
+}
+
+impl Entry {
+  pub(crate) fn remotes(&self) -> BTreeMap<String, String> {
+    let mut remotes = self.remotes.clone();
+    remotes.insert(self.origin.clone(), self.url.clone());
+    remotes
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse() {
+    let manifest = serde_yaml::from_str::<raw::Manifest>(
+      "
+repositories:
+- project: just
+- provider: github
+  user: casey
+  project: src
+  name: github/casey/src
+  origin: upstream
+  remotes:
+    backup: git@example.com:casey/src.git
+",
+    )
+    .unwrap();
+
+    assert_eq!(manifest.repositories.len(), 2);
+    assert_eq!(manifest.repositories[0].provider, None);
+    assert_eq!(
+      manifest.repositories[1].name.as_deref(),
+      Some("github/casey/src")
+    );
+    assert_eq!(manifest.repositories[1].origin.as_deref(), Some("upstream"));
+    assert_eq!(manifest.repositories[1].remotes.len(), 1);
+  }
+}
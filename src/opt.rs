@@ -1,7 +1,5 @@
 use crate::common::*;
 
-use Opt::*;
-
 #[derive(StructOpt)]
 pub(crate) enum Opt {
   Add {
@@ -9,51 +7,160 @@ pub(crate) enum Opt {
     spec: Vec<String>,
     #[structopt(long = "name")]
     name: Option<String>,
+    #[structopt(long = "protocol")]
+    protocol: Option<String>,
+  },
+  Status {
+    #[structopt(flatten)]
+    select: Select,
+    #[structopt(long = "strict")]
+    strict: bool,
+    #[structopt(long = "format", default_value = "human")]
+    format: Format,
   },
-  Status,
   Get {
     #[structopt(long = "tmp")]
     tmp: bool,
+    #[structopt(long = "protocol")]
+    protocol: Option<String>,
+    #[structopt(flatten)]
+    options: CloneOptions,
     #[structopt(required = true, min_values = 1, max_values = 3)]
     spec: Vec<String>,
   },
   Remote {
     #[structopt(required = true, min_values = 1, max_values = 3)]
     spec: Vec<String>,
+    #[structopt(long = "protocol")]
+    protocol: Option<String>,
   },
   Init {
     #[structopt(long = "force")]
     force: bool,
   },
   Push {
-    remote: String,
-    #[structopt(long = "force")]
-    force: bool,
+    #[structopt(flatten)]
+    push: Push,
+  },
+  Fetch {
+    #[structopt(flatten)]
+    select: Select,
+    #[structopt(long = "strict")]
+    strict: bool,
+  },
+  Pull {
+    #[structopt(flatten)]
+    select: Select,
+    #[structopt(long = "strict")]
+    strict: bool,
+  },
+  Mirror {
+    #[structopt(flatten)]
+    select: Select,
+    #[structopt(required = true, min_values = 1)]
+    providers: Vec<String>,
+    #[structopt(long = "fix")]
+    fix: bool,
+  },
+  Sync {
+    #[structopt(long = "manifest")]
+    manifest: Option<PathBuf>,
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+  },
+  Export {
+    #[structopt(long = "json")]
+    json: bool,
+    #[structopt(long = "output")]
+    output: Option<PathBuf>,
+  },
+  Which {
+    target: Option<String>,
+  },
+  Url {
+    #[structopt(flatten)]
+    link: Link,
+    #[structopt(long = "open")]
+    open: bool,
+  },
+  Browse {
+    #[structopt(flatten)]
+    link: Link,
   },
   All {
+    #[structopt(flatten)]
+    select: Select,
+    #[structopt(long = "strict")]
+    strict: bool,
+    #[structopt(long = "jobs")]
+    jobs: Option<usize>,
+    #[structopt(long = "prefix")]
+    prefix: bool,
+    #[structopt(long = "keep-going")]
+    keep_going: bool,
+    #[structopt(required = true)]
     command: Vec<String>,
   },
 }
 
 impl Opt {
   pub(crate) fn run(self) -> Result<(), Error> {
+    use Opt::*;
+
     let config = Config::load()?;
 
     match self {
-      Add { spec, name } => Self::add(config, spec, name),
-      All { command } => Self::all(config, &command),
-      Status => Self::status(config),
-      Remote { spec } => Self::remote(config, spec),
-      Get { tmp, spec } => Self::get(config, tmp, spec),
+      Add {
+        spec,
+        name,
+        protocol,
+      } => Self::add(config, spec, name, protocol),
+      All {
+        select,
+        strict,
+        jobs,
+        prefix,
+        keep_going,
+        command,
+      } => Self::all(config, select, strict, jobs, prefix, keep_going, &command),
+      Fetch { select, strict } => Self::fetch(config, select, strict),
+      Opt::Pull { select, strict } => Self::pull(config, select, strict),
+      Opt::Status {
+        select,
+        strict,
+        format,
+      } => Self::status(config, select, strict, format),
+      Mirror {
+        select,
+        providers,
+        fix,
+      } => Self::mirror(config, select, providers, fix),
+      Sync { manifest, dry_run } => Self::sync(config, manifest, dry_run),
+      Export { json, output } => Self::export(config, json, output),
+      Which { target } => Self::which(config, target),
+      Url { link, open } => Self::url(config, link, open),
+      Browse { link } => Self::url(config, link, true),
+      Remote { spec, protocol } => Self::remote(config, spec, protocol),
+      Get {
+        tmp,
+        protocol,
+        options,
+        spec,
+      } => Self::get(config, tmp, protocol, options, spec),
       Init { force } => Self::init(force),
-      Push { force, remote } => Self::push(config, remote, force),
+      Opt::Push { push } => push.run(config),
     }
   }
 
-  fn add(config: Config, spec: Vec<String>, name: Option<String>) -> Result<(), Error> {
+  fn add(
+    config: Config,
+    spec: Vec<String>,
+    name: Option<String>,
+    protocol: Option<String>,
+  ) -> Result<(), Error> {
     let spec = config.spec(spec)?;
 
-    let remote = spec.remote()?;
+    let remote = spec.remote(protocol.as_deref())?;
 
     let name = name.as_deref().unwrap_or(&spec.provider.name);
 
@@ -64,31 +171,181 @@ impl Opt {
     Ok(())
   }
 
-  fn all(config: Config, command: &[String]) -> Result<(), Error> {
-    let src = Src::load(config.srcdir())?;
+  fn all(
+    config: Config,
+    select: Select,
+    strict: bool,
+    jobs: Option<usize>,
+    prefix: bool,
+    keep_going: bool,
+    command: &[String],
+  ) -> Result<(), Error> {
+    let mut src = Src::load(&config)?;
+
+    src.select(&select, &config)?;
+
+    src.print_failures();
 
-    src.all(command)?;
+    if strict {
+      src.strict()?;
+    }
+
+    if jobs.is_none() && !prefix {
+      src.all(command, keep_going)
+    } else {
+      src.all_parallel(command, jobs.unwrap_or(1), prefix, keep_going)
+    }
+  }
+
+  fn status(config: Config, select: Select, strict: bool, format: Format) -> Result<(), Error> {
+    let mut src = Src::load(&config)?;
+
+    src.select(&select, &config)?;
+
+    match format {
+      Format::Human => src.print_status(),
+      Format::Json => println!("{}", src.report().json()?),
+      Format::Porcelain => print!("{}", src.report().porcelain()),
+    }
+
+    if strict {
+      src.strict()?;
+    }
 
     Ok(())
   }
 
-  fn status(config: Config) -> Result<(), Error> {
-    let src = Src::load(config.srcdir())?;
+  fn mirror(
+    config: Config,
+    select: Select,
+    providers: Vec<String>,
+    fix: bool,
+  ) -> Result<(), Error> {
+    let mut src = Src::load(&config)?;
+
+    src.select(&select, &config)?;
+
+    src.print_failures();
+
+    src.mirror(&config, &providers, fix)
+  }
+
+  fn fetch(config: Config, select: Select, strict: bool) -> Result<(), Error> {
+    let mut src = Src::load(&config)?;
+
+    src.select(&select, &config)?;
+
+    if strict {
+      src.print_failures();
+      src.strict()?;
+    }
+
+    let result = src.fetch_all();
 
     src.print_status();
 
+    result
+  }
+
+  fn pull(config: Config, select: Select, strict: bool) -> Result<(), Error> {
+    let mut src = Src::load(&config)?;
+
+    src.select(&select, &config)?;
+
+    src.print_failures();
+
+    if strict {
+      src.strict()?;
+    }
+
+    src.pull_all()
+  }
+
+  fn sync(config: Config, manifest: Option<PathBuf>, dry_run: bool) -> Result<(), Error> {
+    let manifest = Manifest::load(&config, manifest)?;
+
+    let mut src = Src::load(&config)?;
+
+    src.print_failures();
+
+    src.sync(&config, &manifest, dry_run)
+  }
+
+  fn export(config: Config, json: bool, output: Option<PathBuf>) -> Result<(), Error> {
+    let src = Src::load(&config)?;
+
+    src.print_failures();
+
+    let manifest = src.export(&config);
+
+    let text = if json {
+      serde_json::to_string_pretty(&manifest).context(error::Json)? + "\n"
+    } else {
+      serde_yaml::to_string(&manifest).context(error::Yaml)? + "\n"
+    };
+
+    match output {
+      Some(path) => fs::write(&path, text).context(error::Io { path: &path })?,
+      None => print!("{}", text),
+    }
+
     Ok(())
   }
 
-  fn remote(config: Config, spec: Vec<String>) -> Result<(), Error> {
+  fn which(config: Config, target: Option<String>) -> Result<(), Error> {
+    let target = target.unwrap_or_else(|| ".".to_owned());
+
+    if !Path::new(&target).exists() {
+      let spec = config
+        .identify(&target)
+        .ok_or_else(|| Error::UnknownRemote {
+          url: target.clone(),
+        })?;
+
+      println!("{}", spec);
+
+      return Ok(());
+    }
+
+    let repo = Repo::discover(Path::new(&target))?;
+
+    for (name, url) in repo.remotes() {
+      match config.identify(url) {
+        Some(spec) => println!("{}: {}", name, spec),
+        None => println!("{}: {} (no matching provider)", name, url),
+      }
+    }
+
+    Ok(())
+  }
+
+  fn url(config: Config, link: Link, open: bool) -> Result<(), Error> {
+    let url = link.url(&config)?;
+
+    println!("{}", url);
+
+    if open {
+      Repo::command_status(vec![config.opener().into(), url.into()])?;
+    }
+
+    Ok(())
+  }
+
+  fn remote(config: Config, spec: Vec<String>, protocol: Option<String>) -> Result<(), Error> {
     let spec = config.spec(spec)?;
 
-    println!("{}", spec.remote()?);
+    println!("{}", spec.remote(protocol.as_deref())?);
 
     Ok(())
   }
 
-  fn get(config: Config, tmp: bool, spec: Vec<String>) -> Result<(), Error> {
+  fn get(
+    config: Config,
+    tmp: bool,
+    protocol: Option<String>,
+    options: CloneOptions,
+    spec: Vec<String>,
+  ) -> Result<(), Error> {
     let spec = config.spec(spec)?;
 
     let dst = if tmp {
@@ -101,14 +358,20 @@ impl Opt {
       return Err(Error::DestinationExists { destination: dst });
     }
 
-    Repo::clone(&spec.provider.name, &spec.remote()?, &dst)?;
+    Repo::clone(
+      &spec.provider.name,
+      &spec.remote(protocol.as_deref())?,
+      &dst,
+      &options.or(spec.provider.clone_options()),
+      config.clone_backend(),
+    )?;
 
     Ok(())
   }
 
   fn init(force: bool) -> Result<(), Error> {
     let path = xdg::BaseDirectories::with_prefix("src")
-      .context(error::BaseDirectoriesError)?
+      .context(error::BaseDirectories)?
       .place_config_file(Config::filename())
       .context(error::ConfigPlace)?;
 
@@ -122,17 +385,4 @@ impl Opt {
 
     Ok(())
   }
-
-  fn push(config: Config, remote: String, force: bool) -> Result<(), Error> {
-    let src = Src::load(config.srcdir())?;
-
-    if !force && src.is_dirty() {
-      src.print_status();
-      return Err(Error::PushDirty);
-    }
-
-    src.push_all(&remote)?;
-
-    Ok(())
-  }
 }
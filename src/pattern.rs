@@ -0,0 +1,150 @@
+use crate::common::*;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+  Literal(String),
+  User,
+  Project,
+}
+
+pub(crate) struct Pattern {
+  host: String,
+  tokens: Vec<Token>,
+}
+
+impl Pattern {
+  pub(crate) const USER: &'static str = "\u{0}user\u{0}";
+  pub(crate) const PROJECT: &'static str = "\u{0}project\u{0}";
+
+  pub(crate) fn new(rendered: &str) -> Pattern {
+    let address = Address::parse(rendered);
+
+    let mut tokens = Vec::new();
+    let mut rest = address.path.as_str();
+
+    loop {
+      let next = [(Self::USER, Token::User), (Self::PROJECT, Token::Project)]
+        .iter()
+        .filter_map(|(sentinel, _)| rest.find(sentinel).map(|i| (i, *sentinel)))
+        .min();
+
+      match next {
+        Some((i, sentinel)) => {
+          if i > 0 {
+            tokens.push(Token::Literal(rest[..i].to_owned()));
+          }
+          tokens.push(if sentinel == Self::USER {
+            Token::User
+          } else {
+            Token::Project
+          });
+          rest = &rest[i + sentinel.len()..];
+        }
+        None => {
+          if !rest.is_empty() {
+            tokens.push(Token::Literal(rest.to_owned()));
+          }
+          break;
+        }
+      }
+    }
+
+    Pattern {
+      host: address.host,
+      tokens,
+    }
+  }
+
+  pub(crate) fn matches(&self, url: &str) -> Option<(Option<String>, String)> {
+    let address = Address::parse(url);
+
+    if address.host != self.host {
+      return None;
+    }
+
+    let mut rest = address.path.as_str();
+    let mut user = None;
+    let mut project = None;
+
+    for (i, token) in self.tokens.iter().enumerate() {
+      match token {
+        Token::Literal(literal) => rest = rest.strip_prefix(literal.as_str())?,
+        Token::User | Token::Project => {
+          let end = match self.tokens.get(i + 1) {
+            Some(Token::Literal(literal)) => rest.find(literal.as_str())?,
+            Some(_) => return None,
+            None => rest.len(),
+          };
+
+          let value = &rest[..end];
+
+          if value.is_empty() || (*token == Token::User && value.contains('/')) {
+            return None;
+          }
+
+          if *token == Token::User {
+            user = Some(value.to_owned());
+          } else {
+            project = Some(value.to_owned());
+          }
+
+          rest = &rest[end..];
+        }
+      }
+    }
+
+    if !rest.is_empty() {
+      return None;
+    }
+
+    Some((user, project?))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pattern(template: &str) -> Pattern {
+    Pattern::new(
+      &template
+        .replace("{{user}}", Pattern::USER)
+        .replace("{{project}}", Pattern::PROJECT),
+    )
+  }
+
+  fn user_project(user: &str, project: &str) -> Option<(Option<String>, String)> {
+    Some((Some(user.to_owned()), project.to_owned()))
+  }
+
+  #[test]
+  fn matches() {
+    let github = pattern("git@github.com:{{user}}/{{project}}.git");
+
+    assert_eq!(
+      github.matches("git@github.com:casey/just.git"),
+      user_project("casey", "just")
+    );
+    assert_eq!(
+      github.matches("https://github.com/casey/just"),
+      user_project("casey", "just")
+    );
+    assert_eq!(github.matches("https://gitlab.com/casey/just"), None);
+    assert_eq!(github.matches("https://github.com/casey"), None);
+
+    let gitea = pattern("ssh://git@git.example.com:2222/mirrors/{{user}}-{{project}}.git");
+
+    assert_eq!(
+      gitea.matches("https://git.example.com/mirrors/casey-just"),
+      user_project("casey", "just")
+    );
+    assert_eq!(gitea.matches("https://git.example.com/casey/just"), None);
+
+    let fixed = pattern("git@example.com:team/{{project}}.git");
+
+    assert_eq!(
+      fixed.matches("git@example.com:team/tool.git"),
+      Some((None, "tool".to_owned()))
+    );
+  }
+}
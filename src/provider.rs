@@ -1,39 +1,383 @@
 use crate::common::*;
 
-const REMOTE_TEMPLATE: &str = "remote";
+const DEFAULT_TEMPLATE: &str = "default";
+
+const WEB_TEMPLATE: &str = "web";
 
 pub(crate) struct Provider {
   pub(crate) name: String,
   pub(crate) default_user: Option<String>,
-  pub(crate) remote_template: Tera,
+  default_template: String,
+  templates: Tera,
+  patterns: Vec<Pattern>,
+  web_template: Option<Tera>,
+  context: tera::Context,
+  clone_options: CloneOptions,
 }
 
 impl Provider {
-  pub(crate) fn from_raw(name: &str, raw: raw::Provider) -> Result<Provider, Error> {
-    let mut remote_template = Tera::default();
-    remote_template
-      .add_raw_template(REMOTE_TEMPLATE, &raw.remote_template)
-      .context(error::Template { name })?;
+  pub(crate) fn from_raw(
+    name: &str,
+    raw: raw::Provider,
+    globals: &BTreeMap<String, String>,
+    env: &BTreeMap<String, String>,
+  ) -> Result<Provider, Error> {
+    let mut variables = globals.clone();
+    variables.extend(raw.variables);
+
+    let mut context = tera::Context::new();
+    context.insert("env", env);
+    for (key, value) in &variables {
+      context.insert(key, value);
+    }
+    context.insert("variables", &variables);
+
+    let mut sources = raw.templates;
+
+    let default_template = match (raw.remote_template, raw.default_template) {
+      (Some(remote_template), default_template) => {
+        let default_template = default_template.unwrap_or_else(|| DEFAULT_TEMPLATE.to_owned());
+        if sources.contains_key(&default_template) {
+          return Err(Error::DuplicateTemplate {
+            provider: name.to_owned(),
+            template: default_template,
+          });
+        }
+        sources.insert(default_template.clone(), remote_template);
+        default_template
+      }
+      (None, Some(default_template)) => default_template,
+      (None, None) if sources.len() == 1 => sources.keys().next().unwrap().clone(),
+      (None, None) => {
+        return Err(Error::DefaultTemplate {
+          provider: name.to_owned(),
+        })
+      }
+    };
+
+    if !sources.contains_key(&default_template) {
+      return Err(Error::Protocol {
+        provider: name.to_owned(),
+        protocol: default_template,
+      });
+    }
+
+    let mut templates = Tera::default();
+    for (protocol, source) in &sources {
+      templates
+        .add_raw_template(protocol, source)
+        .context(error::Template { name })?;
+    }
+
+    let mut patterns = Vec::new();
+    for protocol in sources.keys() {
+      patterns.push(Pattern::new(&Self::check(
+        &templates, &context, name, protocol,
+      )?));
+    }
+
+    let web_template = match raw.web_template {
+      Some(source) => {
+        let mut web_template = Tera::default();
+        web_template
+          .add_raw_template(WEB_TEMPLATE, &source)
+          .context(error::Template { name })?;
+        // Links always have a branch and commit, and a line only with a file,
+        // so check the web template with each combination that can occur.
+        let mut link = context.clone();
+        link.insert("branch", "HEAD");
+        link.insert("commit", "HEAD");
+        Self::check(&web_template, &link, name, WEB_TEMPLATE)?;
+        link.insert("file", "README.md");
+        Self::check(&web_template, &link, name, WEB_TEMPLATE)?;
+        link.insert("line", &1);
+        Self::check(&web_template, &link, name, WEB_TEMPLATE)?;
+        Some(web_template)
+      }
+      None => None,
+    };
 
     Ok(Provider {
       name: name.to_owned(),
       default_user: raw.default_user,
-      remote_template,
+      default_template,
+      templates,
+      patterns,
+      web_template,
+      context,
+      clone_options: raw.clone,
     })
   }
 
-  pub(crate) fn remote(&self, user: &str, project: &str) -> Result<String, Error> {
-    let mut context = tera::Context::new();
+  pub(crate) fn clone_options(&self) -> &CloneOptions {
+    &self.clone_options
+  }
+
+  pub(crate) fn parse(&self, url: &str) -> Option<(Option<String>, String)> {
+    self
+      .patterns
+      .iter()
+      .find_map(|pattern| pattern.matches(url))
+  }
+
+  pub(crate) fn remote(
+    &self,
+    user: &str,
+    project: &str,
+    protocol: Option<&str>,
+  ) -> Result<String, Error> {
+    let protocol = protocol.unwrap_or(&self.default_template);
+
+    if self.templates.get_template(protocol).is_err() {
+      return Err(Error::Protocol {
+        provider: self.name.clone(),
+        protocol: protocol.to_owned(),
+      });
+    }
+
+    Self::render(
+      &self.templates,
+      &self.context,
+      &self.name,
+      protocol,
+      user,
+      project,
+    )
+  }
+
+  pub(crate) fn web(
+    &self,
+    user: &str,
+    project: &str,
+    context: tera::Context,
+  ) -> Result<String, Error> {
+    let web_template = self
+      .web_template
+      .as_ref()
+      .ok_or_else(|| Error::WebTemplate {
+        provider: self.name.clone(),
+      })?;
+
+    let mut base = self.context.clone();
+    base.extend(context);
+
+    Self::render(web_template, &base, &self.name, WEB_TEMPLATE, user, project)
+  }
+
+  fn render(
+    templates: &Tera,
+    context: &tera::Context,
+    provider: &str,
+    template: &str,
+    user: &str,
+    project: &str,
+  ) -> Result<String, Error> {
+    let mut context = context.clone();
     context.insert("user", user);
     context.insert("project", project);
 
-    Ok(
-      self
-        .remote_template
-        .render(REMOTE_TEMPLATE, &context)
-        .context(error::Render {
-          provider: &self.name,
-        })?,
+    templates
+      .render(template, &context)
+      .context(error::Render { provider })
+  }
+
+  /// Render `template` with placeholder `user` and `project` values, so that
+  /// references to undefined variables are reported when the config is loaded.
+  fn check(
+    templates: &Tera,
+    context: &tera::Context,
+    provider: &str,
+    template: &str,
+  ) -> Result<String, Error> {
+    let mut context = context.clone();
+    context.insert("user", Pattern::USER);
+    context.insert("project", Pattern::PROJECT);
+
+    templates.render(template, &context).map_err(|error| {
+      let mut cause: &dyn std::error::Error = &error;
+      while let Some(source) = cause.source() {
+        cause = source;
+      }
+
+      Error::Variable {
+        provider: provider.to_owned(),
+        template: template.to_owned(),
+        message: cause.to_string(),
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn provider(yaml: &str) -> Result<Provider, Error> {
+    Provider::from_raw(
+      "test",
+      serde_yaml::from_str(yaml).unwrap(),
+      &BTreeMap::new(),
+      &BTreeMap::new(),
     )
   }
+
+  #[test]
+  fn remote_template_alias() {
+    let provider = provider("remote-template: git@example.com:{{user}}/{{project}}.git").unwrap();
+
+    assert_eq!(
+      provider.remote("casey", "just", None).unwrap(),
+      "git@example.com:casey/just.git"
+    );
+    assert_eq!(
+      provider.remote("casey", "just", Some("default")).unwrap(),
+      "git@example.com:casey/just.git"
+    );
+  }
+
+  #[test]
+  fn protocols() {
+    let provider = provider(
+      "
+default-template: ssh
+templates:
+  ssh: git@example.com:{{user}}/{{project}}.git
+  https: https://example.com/{{user}}/{{project}}.git
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+      provider.remote("casey", "just", None).unwrap(),
+      "git@example.com:casey/just.git"
+    );
+    assert_eq!(
+      provider.remote("casey", "just", Some("https")).unwrap(),
+      "https://example.com/casey/just.git"
+    );
+    assert!(provider.remote("casey", "just", Some("web")).is_err());
+  }
+
+  #[test]
+  fn missing_default() {
+    assert!(provider(
+      "
+templates:
+  ssh: git@example.com:{{user}}/{{project}}.git
+  https: https://example.com/{{user}}/{{project}}.git
+",
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn web() {
+    let provider = provider(
+      "
+remote-template: git@example.com:{{user}}/{{project}}.git
+web-template: 'https://example.com/{{user}}/{{project}}{% if file %}/blob/{{commit | default(value=\"HEAD\")}}/{{file}}{% if line %}#L{{line}}{% endif %}{% endif %}'
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+      provider.web("casey", "just", tera::Context::new()).unwrap(),
+      "https://example.com/casey/just"
+    );
+
+    let mut context = tera::Context::new();
+    context.insert("file", "src/main.rs");
+    context.insert("line", &7);
+    assert_eq!(
+      provider.web("casey", "just", context.clone()).unwrap(),
+      "https://example.com/casey/just/blob/HEAD/src/main.rs#L7"
+    );
+
+    context.insert("commit", "abc123");
+    assert_eq!(
+      provider.web("casey", "just", context).unwrap(),
+      "https://example.com/casey/just/blob/abc123/src/main.rs#L7"
+    );
+
+    assert!(self::provider(
+      "
+remote-template: git@example.com:{{user}}/{{project}}.git
+web-template: 'https://example.com/{{user}}/{{project}}/tree/{{branch}}'
+",
+    )
+    .is_ok());
+
+    assert!(self::provider(
+      "
+remote-template: git@example.com:{{user}}/{{project}}.git
+web-template: 'https://example.com/{{user}}/{{project}}/blob/HEAD/{{file}}'
+",
+    )
+    .is_err());
+
+    assert!(self::provider(
+      "
+remote-template: git@example.com:{{user}}/{{project}}.git
+web-template: 'https://example.com/{{user}}/{{project}}{% if file %}/blob/HEAD/{{file}}#L{{line}}{% endif %}'
+",
+    )
+    .is_err());
+
+    assert!(
+      self::provider("remote-template: git@example.com:{{user}}/{{project}}.git")
+        .unwrap()
+        .web("casey", "just", tera::Context::new())
+        .is_err()
+    );
+  }
+
+  #[test]
+  fn variables() {
+    let mut globals = BTreeMap::new();
+    globals.insert("host".to_owned(), "example.com".to_owned());
+    globals.insert("port".to_owned(), "22".to_owned());
+
+    let provider = Provider::from_raw(
+      "test",
+      serde_yaml::from_str(
+        "
+remote-template: 'ssh://git@{{host}}:{{port}}/{{variables[\"org-prefix\"]}}{{user}}/{{project}}.git'
+variables:
+  port: '2222'
+  org-prefix: acme-
+",
+      )
+      .unwrap(),
+      &globals,
+      &BTreeMap::new(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      provider.remote("casey", "just", None).unwrap(),
+      "ssh://git@example.com:2222/acme-casey/just.git"
+    );
+
+    let mut env = BTreeMap::new();
+    env.insert("HOST".to_owned(), "example.org".to_owned());
+
+    assert_eq!(
+      Provider::from_raw(
+        "test",
+        serde_yaml::from_str("remote-template: 'https://{{env.HOST}}/{{user}}/{{project}}'")
+          .unwrap(),
+        &globals,
+        &env,
+      )
+      .unwrap()
+      .remote("casey", "just", None)
+      .unwrap(),
+      "https://example.org/casey/just"
+    );
+
+    match self::provider("remote-template: 'git@{{host}}:{{user}}/{{project}}.git'") {
+      Err(Error::Variable { template, .. }) => assert_eq!(template, "default"),
+      _ => panic!("undefined variable was not reported"),
+    }
+  }
 }
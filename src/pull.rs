@@ -0,0 +1,28 @@
+use crate::common::*;
+
+pub(crate) enum Pull {
+  Updated { commits: usize },
+  UpToDate,
+  Dirty,
+  Detached,
+  NoUpstream,
+  Diverged { upstream: String },
+}
+
+impl Display for Pull {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Pull::Updated { commits } => write!(
+        f,
+        "updated ({} {})",
+        commits,
+        if *commits == 1 { "commit" } else { "commits" }
+      ),
+      Pull::UpToDate => write!(f, "up to date"),
+      Pull::Dirty => write!(f, "skipped, dirty"),
+      Pull::Detached => write!(f, "skipped, detached HEAD"),
+      Pull::NoUpstream => write!(f, "skipped, no upstream"),
+      Pull::Diverged { upstream } => write!(f, "diverged from {}", upstream),
+    }
+  }
+}
@@ -0,0 +1,77 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+pub(crate) struct Push {
+  #[structopt(flatten)]
+  select: Select,
+  remote: String,
+  #[structopt(long = "force")]
+  force: bool,
+  #[structopt(long = "strict")]
+  strict: bool,
+  #[structopt(long = "include-stashes")]
+  include_stashes: bool,
+  #[structopt(long = "dry-run")]
+  dry_run: bool,
+  #[structopt(long = "only", number_of_values = 1)]
+  only: Vec<String>,
+  #[structopt(long = "skip-dirty", conflicts_with = "force")]
+  skip_dirty: bool,
+  #[structopt(long = "create")]
+  create: bool,
+}
+
+impl Push {
+  pub(crate) fn run(self, config: Config) -> Result<(), Error> {
+    let mut src = Src::load(&config)?;
+
+    src.select(&self.select, &config)?;
+
+    if !self.only.is_empty() {
+      src.only(&self.only)?;
+    }
+
+    if !self.create {
+      let skipped = src.skip_missing_remote(&self.remote);
+
+      if !skipped.is_empty() {
+        eprintln!("Skipping repositories without remote `{}`:", self.remote);
+        for name in skipped {
+          eprintln!("{}", name);
+        }
+      }
+    }
+
+    if self.skip_dirty {
+      let skipped = src.skip_dirty(self.include_stashes);
+
+      if !skipped.is_empty() {
+        eprintln!("Skipping dirty repositories:");
+        for name in skipped {
+          eprintln!("{}", name);
+        }
+      }
+    }
+
+    if !self.force && !self.dry_run && src.is_dirty(self.include_stashes) {
+      src.print_status();
+      return Err(Error::PushDirty);
+    }
+
+    src.print_failures();
+
+    if self.strict {
+      src.strict()?;
+    }
+
+    let pending = if self.create {
+      src.create_remote(&config, &self.remote, self.dry_run)?
+    } else {
+      BTreeMap::new()
+    };
+
+    src.push_all(&self.remote, self.dry_run, &pending)?;
+
+    Ok(())
+  }
+}
@@ -9,13 +9,58 @@ pub(crate) struct Config {
   pub(crate) default_user: Option<String>,
   pub(crate) srcdir: PathBuf,
   pub(crate) tmpdir: PathBuf,
+  #[serde(rename = "max-depth")]
+  pub(crate) max_depth: Option<usize>,
+  pub(crate) jobs: Option<usize>,
+  #[serde(default)]
+  pub(crate) groups: BTreeMap<String, Vec<String>>,
+  pub(crate) opener: Option<String>,
+  #[serde(rename = "clone-backend")]
+  pub(crate) clone_backend: Option<Backend>,
+  #[serde(default)]
+  pub(crate) variables: BTreeMap<String, String>,
   pub(crate) providers: BTreeMap<String, raw::Provider>,
 }
 
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub(crate) struct Manifest {
+  pub(crate) repositories: Vec<raw::Entry>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub(crate) struct Entry {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) provider: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) user: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) project: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) url: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) origin: Option<String>,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub(crate) remotes: BTreeMap<String, String>,
+}
+
 #[derive(Deserialize)]
 pub(crate) struct Provider {
   #[serde(rename = "default-user")]
   pub(crate) default_user: Option<String>,
   #[serde(rename = "remote-template")]
-  pub(crate) remote_template: String,
+  pub(crate) remote_template: Option<String>,
+  #[serde(default)]
+  pub(crate) templates: BTreeMap<String, String>,
+  #[serde(rename = "default-template")]
+  pub(crate) default_template: Option<String>,
+  #[serde(rename = "web-template")]
+  pub(crate) web_template: Option<String>,
+  #[serde(default)]
+  pub(crate) clone: CloneOptions,
+  #[serde(default)]
+  pub(crate) variables: BTreeMap<String, String>,
 }
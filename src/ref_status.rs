@@ -0,0 +1,59 @@
+use crate::common::*;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum RefStatus {
+  UpToDate,
+  FastForward,
+  Forced,
+  New,
+  Deleted,
+  Rejected { reason: String },
+  RemoteRejected { reason: String },
+}
+
+impl RefStatus {
+  /// Parse the flag and summary fields of a line of `git push --porcelain`
+  /// output.
+  pub(crate) fn parse(flag: &str, summary: &str) -> Option<RefStatus> {
+    let reason = || {
+      summary
+        .find('(')
+        .map(|start| summary[start + 1..].trim_end_matches(')').to_owned())
+        .unwrap_or_default()
+    };
+
+    match flag {
+      "=" => Some(RefStatus::UpToDate),
+      " " => Some(RefStatus::FastForward),
+      "+" => Some(RefStatus::Forced),
+      "*" => Some(RefStatus::New),
+      "-" => Some(RefStatus::Deleted),
+      "!" if summary.starts_with("[remote rejected]") => {
+        Some(RefStatus::RemoteRejected { reason: reason() })
+      }
+      "!" => Some(RefStatus::Rejected { reason: reason() }),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn is_rejected(&self) -> bool {
+    matches!(
+      self,
+      RefStatus::Rejected { .. } | RefStatus::RemoteRejected { .. }
+    )
+  }
+}
+
+impl Display for RefStatus {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      RefStatus::UpToDate => write!(f, "up to date"),
+      RefStatus::FastForward => write!(f, "fast-forward"),
+      RefStatus::Forced => write!(f, "forced update"),
+      RefStatus::New => write!(f, "created"),
+      RefStatus::Deleted => write!(f, "deleted"),
+      RefStatus::Rejected { reason } => write!(f, "rejected ({})", reason),
+      RefStatus::RemoteRejected { reason } => write!(f, "remote rejected ({})", reason),
+    }
+  }
+}
@@ -0,0 +1,89 @@
+use crate::common::*;
+
+pub(crate) struct RefUpdate {
+  pub(crate) from: String,
+  pub(crate) to: String,
+  pub(crate) status: RefStatus,
+}
+
+impl RefUpdate {
+  pub(crate) fn parse_porcelain(output: &str) -> Vec<RefUpdate> {
+    output
+      .lines()
+      .filter_map(|line| {
+        let mut fields = line.splitn(3, '\t');
+        let flag = fields.next()?;
+        let (from, to) = fields.next()?.split_once(':')?;
+        let summary = fields.next().unwrap_or_default();
+
+        Some(RefUpdate {
+          from: from.to_owned(),
+          to: to.to_owned(),
+          status: RefStatus::parse(flag, summary)?,
+        })
+      })
+      .collect()
+  }
+
+  pub(crate) fn name(&self) -> &str {
+    let name = if self.to.is_empty() {
+      &self.from
+    } else {
+      &self.to
+    };
+
+    name
+      .strip_prefix("refs/heads/")
+      .or_else(|| name.strip_prefix("refs/"))
+      .unwrap_or(name)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_porcelain() {
+    let output = "To github.com:casey/just.git
+=\trefs/heads/master:refs/heads/master\t[up to date]
+ \trefs/heads/dev:refs/heads/dev\t77bb071..2fef3fa
++\trefs/heads/wip:refs/heads/wip\t2fef3fa...77bb071 (forced update)
+*\trefs/heads/feature:refs/heads/feature\t[new branch]
+*\trefs/tags/v1.0:refs/tags/v1.0\t[new tag]
+-\t:refs/heads/old\t[deleted]
+!\trefs/heads/main:refs/heads/main\t[rejected] (non-fast-forward)
+!\trefs/heads/locked:refs/heads/locked\t[remote rejected] (pre-receive hook declined)
+Done
+";
+
+    let updates = RefUpdate::parse_porcelain(output)
+      .into_iter()
+      .map(|update| (update.name().to_owned(), update.status))
+      .collect::<Vec<(String, RefStatus)>>();
+
+    assert_eq!(
+      updates,
+      &[
+        ("master".to_owned(), RefStatus::UpToDate),
+        ("dev".to_owned(), RefStatus::FastForward),
+        ("wip".to_owned(), RefStatus::Forced),
+        ("feature".to_owned(), RefStatus::New),
+        ("tags/v1.0".to_owned(), RefStatus::New),
+        ("old".to_owned(), RefStatus::Deleted),
+        (
+          "main".to_owned(),
+          RefStatus::Rejected {
+            reason: "non-fast-forward".to_owned()
+          }
+        ),
+        (
+          "locked".to_owned(),
+          RefStatus::RemoteRejected {
+            reason: "pre-receive hook declined".to_owned()
+          }
+        ),
+      ]
+    );
+  }
+}
@@ -2,17 +2,40 @@ use crate::common::*;
 
 pub(crate) struct Repo {
   path: PathBuf,
+  workdir: PathBuf,
   name: String,
   state: git2::RepositoryState,
   files: BTreeMap<String, Status>,
   head: String,
+  commit: Option<String>,
+  branches: Vec<Branch>,
+  remotes: BTreeMap<String, String>,
+  stashes: usize,
+  stash: Option<String>,
+  shallow: bool,
+  partial: bool,
 }
 
 impl Repo {
+  const STATES: &'static [git2::RepositoryState] = &[
+    git2::RepositoryState::Clean,
+    git2::RepositoryState::Merge,
+    git2::RepositoryState::Revert,
+    git2::RepositoryState::RevertSequence,
+    git2::RepositoryState::CherryPick,
+    git2::RepositoryState::CherryPickSequence,
+    git2::RepositoryState::Bisect,
+    git2::RepositoryState::Rebase,
+    git2::RepositoryState::RebaseInteractive,
+    git2::RepositoryState::RebaseMerge,
+    git2::RepositoryState::ApplyMailbox,
+    git2::RepositoryState::ApplyMailboxOrRebase,
+  ];
+
   pub(crate) fn new(path: &Path) -> Result<Repo, Error> {
     let context = error::Git { path };
 
-    let repo = git2::Repository::open(path).context(context)?;
+    let mut repo = git2::Repository::open(path).context(context)?;
 
     let context = error::Git { path };
 
@@ -30,17 +53,23 @@ impl Repo {
     status_options.include_ignored(false);
     status_options.include_untracked(true);
 
-    let files = repo
-      .statuses(Some(&mut status_options))
-      .context(context)?
-      .iter()
-      .map(|status_entry| {
-        (
-          String::from_utf8_lossy(status_entry.path_bytes()).into_owned(),
-          Status::new(status_entry.status()),
-        )
-      })
-      .collect();
+    let files = if repo.is_bare() {
+      BTreeMap::new()
+    } else {
+      repo
+        .statuses(Some(&mut status_options))
+        .context(context)?
+        .iter()
+        .map(|status_entry| {
+          (
+            String::from_utf8_lossy(status_entry.path_bytes()).into_owned(),
+            Status::new(status_entry.status()),
+          )
+        })
+        .collect()
+    };
+
+    let shallow = repo.is_shallow();
 
     fn head(repo: &git2::Repository) -> Result<String, git2::Error> {
       for branch in repo.branches(Some(git2::BranchType::Local))? {
@@ -72,21 +101,94 @@ impl Repo {
 
     let head = head(&repo).context(context)?;
 
+    let commit = repo
+      .head()
+      .and_then(|head| head.peel_to_commit())
+      .map(|commit| commit.id().to_string())
+      .ok();
+
+    let branches = Branch::load(&repo).context(error::Git { path })?;
+
+    let mut remotes = BTreeMap::new();
+    for name in repo
+      .remotes()
+      .context(error::Git { path })?
+      .iter()
+      .flatten()
+    {
+      let remote = repo.find_remote(name).context(error::Git { path })?;
+      if let Some(url) = remote.url() {
+        remotes.insert(name.to_owned(), url.to_owned());
+      }
+    }
+
+    let partial = {
+      let config = repo.config().context(error::Git { path })?;
+
+      config.get_string("extensions.partialclone").is_ok()
+        || remotes.keys().any(|name| {
+          config
+            .get_bool(&format!("remote.{}.promisor", name))
+            .unwrap_or(false)
+        })
+    };
+
+    let mut stashes = 0;
+    let mut stash = None;
+
+    repo
+      .stash_foreach(|index, message, _| {
+        if index == 0 {
+          stash = Some(message.to_owned());
+        }
+        stashes += 1;
+        true
+      })
+      .context(error::Git { path })?;
+
     let path = repo.path().to_owned();
 
+    let workdir = repo
+      .workdir()
+      .unwrap_or_else(|| repo.path())
+      .components()
+      .collect();
+
     Ok(Repo {
+      workdir,
       name,
       state,
       files,
       head,
+      commit,
+      branches,
+      remotes,
+      stashes,
+      stash,
+      shallow,
+      partial,
       path,
     })
   }
 
+  pub(crate) fn discover(path: &Path) -> Result<Repo, Error> {
+    let repo = git2::Repository::discover(path).context(error::Git { path })?;
+
+    Self::new(repo.workdir().unwrap_or_else(|| repo.path()))
+  }
+
+  pub(crate) fn is_repo(path: &Path) -> bool {
+    path.join(".git").exists() || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+  }
+
+  pub(crate) fn with_name(self, name: String) -> Repo {
+    Repo { name, ..self }
+  }
+
   pub(crate) fn run(&self, command: Vec<OsString>) -> Result<(), Error> {
     let status = Command::new(&command[0])
       .args(&command[1..])
-      .current_dir(&self.path.parent().unwrap())
+      .current_dir(&self.workdir)
       .status()
       .context(error::CommandInvocation {
         command: command.clone(),
@@ -99,6 +201,73 @@ impl Repo {
     Ok(())
   }
 
+  pub(crate) fn run_buffered(&self, command: Vec<OsString>) -> Result<(), Error> {
+    let output = Command::new(&command[0])
+      .args(&command[1..])
+      .current_dir(&self.workdir)
+      .output()
+      .context(error::CommandInvocation {
+        command: command.clone(),
+      })?;
+
+    {
+      let stdout = io::stdout();
+      let _stdout = stdout.lock();
+      let stderr = io::stderr();
+      let _stderr = stderr.lock();
+
+      println!("==> {} <==", self.name);
+      print!("{}", String::from_utf8_lossy(&output.stdout));
+      eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    if !output.status.success() {
+      return Err(Error::CommandStatus {
+        command,
+        status: output.status,
+      });
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn run_prefixed(&self, command: Vec<OsString>) -> Result<(), Error> {
+    let mut child = Command::new(&command[0])
+      .args(&command[1..])
+      .current_dir(&self.workdir)
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .spawn()
+      .context(error::CommandInvocation {
+        command: command.clone(),
+      })?;
+
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+
+    thread::scope(|scope| {
+      scope.spawn(|| {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+          eprintln!("{} | {}", self.name, line);
+        }
+      });
+
+      for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        println!("{} | {}", self.name, line);
+      }
+    });
+
+    let status = child.wait().context(error::CommandInvocation {
+      command: command.clone(),
+    })?;
+
+    if !status.success() {
+      return Err(Error::CommandStatus { command, status });
+    }
+
+    Ok(())
+  }
+
   pub(crate) fn command_status(command: Vec<OsString>) -> Result<(), Error> {
     let status = Command::new(&command[0])
       .args(&command[1..])
@@ -114,7 +283,7 @@ impl Repo {
     Ok(())
   }
 
-  pub(crate) fn command_output(command: Vec<OsString>) -> Result<(), Error> {
+  pub(crate) fn command_output(command: Vec<OsString>) -> Result<String, Error> {
     let output = Command::new(&command[0])
       .args(&command[1..])
       .output()
@@ -133,63 +302,379 @@ impl Repo {
       });
     }
 
-    Ok(())
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
   }
 
-  pub(crate) fn push(&self, remote: &str) -> Result<(), Error> {
-    let command: Vec<OsString> = vec![
+  pub(crate) fn push(&self, remote: &str, dry_run: bool) -> Result<Vec<RefUpdate>, Error> {
+    let mut command: Vec<OsString> = vec![
       "git".into(),
       "--git-dir".into(),
       self.path.clone().into(),
       "push".into(),
       "--all".into(),
-      remote.into(),
+    ];
+
+    command.push("--porcelain".into());
+
+    if dry_run {
+      command.push("--dry-run".into());
+    }
+
+    command.push(remote.into());
+
+    match Self::command_output(command) {
+      Ok(stdout) => Ok(RefUpdate::parse_porcelain(&stdout)),
+      // Rejected refs make `git push` fail, but are reported per ref.
+      Err(Error::CommandOutput { stdout, .. })
+        if RefUpdate::parse_porcelain(&stdout)
+          .iter()
+          .any(|update| update.status.is_rejected()) =>
+      {
+        Ok(RefUpdate::parse_porcelain(&stdout))
+      }
+      Err(error) => Err(error),
+    }
+  }
+
+  pub(crate) fn fetch(&mut self) -> Result<(), Error> {
+    let command: Vec<OsString> = vec![
+      "git".into(),
+      "--git-dir".into(),
+      self.path.clone().into(),
+      "fetch".into(),
+      "--all".into(),
     ];
 
     Self::command_output(command)?;
 
+    *self = Self::new(&self.workdir)?.with_name(self.name.clone());
+
     Ok(())
   }
 
-  pub(crate) fn clone(provider: &str, url: &str, into: &Path) -> Result<Repo, Error> {
+  pub(crate) fn pull(&self) -> Result<Pull, Error> {
+    if self.is_dirty(false) {
+      return Ok(Pull::Dirty);
+    }
+
+    let branch = match self.branches().find(|branch| branch.name == self.head) {
+      Some(branch) => branch,
+      None => return Ok(Pull::Detached),
+    };
+
+    let upstream = match &branch.upstream {
+      Some(upstream) => upstream,
+      None => return Ok(Pull::NoUpstream),
+    };
+
+    if branch.behind == 0 {
+      return Ok(Pull::UpToDate);
+    }
+
+    if branch.ahead > 0 {
+      return Ok(Pull::Diverged {
+        upstream: upstream.clone(),
+      });
+    }
+
     let command: Vec<OsString> = vec![
+      "git".into(),
+      "-C".into(),
+      self.workdir.clone().into(),
+      "merge".into(),
+      "--ff-only".into(),
+      upstream.into(),
+    ];
+
+    Self::command_output(command)?;
+
+    Ok(Pull::Updated {
+      commits: branch.behind,
+    })
+  }
+
+  pub(crate) fn clone(
+    provider: &str,
+    url: &str,
+    into: &Path,
+    options: &CloneOptions,
+    backend: Backend,
+  ) -> Result<Repo, Error> {
+    if backend == Backend::Cli || options.requires_cli() {
+      Self::clone_cli(provider, url, into, options)?;
+    } else {
+      Self::clone_git2(provider, url, into, options).context(error::Clone { url })?;
+    }
+
+    Self::new(into)
+  }
+
+  fn clone_cli(
+    provider: &str,
+    url: &str,
+    into: &Path,
+    options: &CloneOptions,
+  ) -> Result<(), Error> {
+    let mut command: Vec<OsString> = vec![
       "git".into(),
       "clone".into(),
       "--origin".into(),
       provider.into(),
-      url.into(),
-      into.into(),
     ];
+    command.extend(options.args());
+    command.push(url.into());
+    command.push(into.into());
 
-    Self::command_status(command)?;
+    Self::command_status(command)
+  }
 
-    Self::new(into)
+  fn clone_git2(
+    provider: &str,
+    url: &str,
+    into: &Path,
+    options: &CloneOptions,
+  ) -> Result<(), git2::Error> {
+    let style = ProgressStyle::default_bar().template("{prefix}: {wide_bar} {pos}/{len} {msg}");
+
+    let bar = ProgressBar::new(0);
+    bar.set_style(style);
+    bar.set_prefix("Receiving");
+
+    let result = Self::clone_git2_with_progress(provider, url, into, options, &bar);
+
+    if result.is_ok() {
+      bar.finish();
+    } else {
+      bar.finish_and_clear();
+    }
+
+    result
+  }
+
+  fn clone_git2_with_progress(
+    provider: &str,
+    url: &str,
+    into: &Path,
+    options: &CloneOptions,
+    bar: &ProgressBar,
+  ) -> Result<(), git2::Error> {
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.progress(|_, current, total| {
+      bar.set_prefix("Checking out");
+      bar.set_message("");
+      bar.set_length(total as u64);
+      bar.set_position(current as u64);
+    });
+
+    let mut builder = git2::build::RepoBuilder::new();
+    builder
+      .fetch_options(Self::fetch_options(bar))
+      .with_checkout(checkout)
+      .remote_create(|repo, _, url| repo.remote(provider, url));
+    options.configure(&mut builder);
+
+    let repo = builder.clone(url, into)?;
+
+    // libgit2 configures the checked out branch to track `origin`, even when
+    // the remote was created under a different name.
+    if !repo.is_bare() {
+      let head = repo.head()?;
+      if let Some(branch) = head.shorthand() {
+        repo
+          .config()?
+          .set_str(&format!("branch.{}.remote", branch), provider)?;
+      }
+    }
+
+    if options.recurse_submodules() {
+      Self::update_submodules(&repo, bar)?;
+    }
+
+    Ok(())
+  }
+
+  fn fetch_options(bar: &ProgressBar) -> git2::FetchOptions<'_> {
+    let mut credentials = Credentials::default();
+
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks
+      .credentials(move |url, username, allowed| credentials.get(url, username, allowed))
+      .transfer_progress(move |progress| {
+        bar.set_prefix("Receiving");
+        bar.set_message(&HumanBytes(progress.received_bytes() as u64).to_string());
+        bar.set_length(progress.total_objects() as u64);
+        bar.set_position(progress.received_objects() as u64);
+        true
+      });
+
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    fetch_options
+  }
+
+  fn update_submodules(repo: &git2::Repository, bar: &ProgressBar) -> Result<(), git2::Error> {
+    for mut submodule in repo.submodules()? {
+      let mut update_options = git2::SubmoduleUpdateOptions::new();
+      update_options.fetch(Self::fetch_options(bar));
+
+      submodule.update(true, Some(&mut update_options))?;
+
+      Self::update_submodules(&submodule.open()?, bar)?;
+    }
+
+    Ok(())
   }
 
   pub(crate) fn name(&self) -> &str {
     &self.name
   }
 
+  /// The final component of the repository's name, used as the project when
+  /// rendering remote URLs.
+  pub(crate) fn project(&self) -> &str {
+    self.name.rsplit('/').next().unwrap_or(&self.name)
+  }
+
   pub(crate) fn head(&self) -> &str {
     &self.head
   }
 
+  pub(crate) fn is_shallow(&self) -> bool {
+    self.shallow
+  }
+
+  pub(crate) fn is_partial(&self) -> bool {
+    self.partial
+  }
+
+  pub(crate) fn commit(&self) -> Option<&str> {
+    self.commit.as_deref()
+  }
+
+  pub(crate) fn workdir(&self) -> &Path {
+    &self.workdir
+  }
+
   pub(crate) fn files(&self) -> impl Iterator<Item = (&String, &Status)> {
     self.files.iter()
   }
 
+  pub(crate) fn remotes(&self) -> &BTreeMap<String, String> {
+    &self.remotes
+  }
+
+  pub(crate) fn set_remote_url(&mut self, name: &str, url: &str) -> Result<(), Error> {
+    let command: Vec<OsString> = vec![
+      "git".into(),
+      "--git-dir".into(),
+      self.path.clone().into(),
+      "remote".into(),
+      "set-url".into(),
+      name.into(),
+      url.into(),
+    ];
+
+    Self::command_output(command)?;
+
+    self.remotes.insert(name.to_owned(), url.to_owned());
+
+    Ok(())
+  }
+
+  pub(crate) fn add_remote(&mut self, name: &str, url: &str) -> Result<(), Error> {
+    let command: Vec<OsString> = vec![
+      "git".into(),
+      "--git-dir".into(),
+      self.path.clone().into(),
+      "remote".into(),
+      "add".into(),
+      name.into(),
+      url.into(),
+    ];
+
+    Self::command_output(command)?;
+
+    self.remotes.insert(name.to_owned(), url.to_owned());
+
+    Ok(())
+  }
+
+  pub(crate) fn branches(&self) -> impl Iterator<Item = &Branch> {
+    self.branches.iter()
+  }
+
+  pub(crate) fn is_unpushed(&self) -> bool {
+    self.branches().any(Branch::is_unpushed)
+  }
+
+  pub(crate) fn is_behind(&self) -> bool {
+    self.branches().any(|branch| branch.behind > 0)
+  }
+
   pub(crate) fn changes(&self) -> usize {
     self.files().count()
   }
 
-  pub(crate) fn is_dirty(&self) -> bool {
-    self.state != git2::RepositoryState::Clean || self.changes() > 0
+  pub(crate) fn stashes(&self) -> usize {
+    self.stashes
+  }
+
+  pub(crate) fn stash(&self) -> Option<&str> {
+    self.stash.as_deref()
+  }
+
+  pub(crate) fn is_dirty(&self, stashes: bool) -> bool {
+    self.state != git2::RepositoryState::Clean
+      || self.changes() > 0
+      || (stashes && self.stashes > 0)
+  }
+
+  pub(crate) fn report(&self) -> report::Repository {
+    report::Repository {
+      name: self.name.clone(),
+      path: self.workdir.clone(),
+      head: self.head.clone(),
+      state: self.state().replace(' ', "-"),
+      dirty: self.is_dirty(false),
+      unpushed: self.is_unpushed(),
+      stashes: self.stashes,
+      stash: self.stash.clone(),
+      shallow: self.shallow,
+      partial: self.partial,
+      files: self
+        .files()
+        .map(|(path, status)| report::File {
+          path: path.clone(),
+          status: status.code(),
+        })
+        .collect(),
+      branches: self.branches().map(Branch::report).collect(),
+    }
+  }
+
+  pub(crate) fn repository_state(&self) -> git2::RepositoryState {
+    self.state
   }
 
   pub(crate) fn state(&self) -> &'static str {
+    Self::state_name(self.state)
+  }
+
+  /// Parse the name of a repository state, with words separated by either
+  /// spaces or dashes.
+  pub(crate) fn parse_state(text: &str) -> Option<git2::RepositoryState> {
+    let text = text.replace('-', " ");
+
+    Self::STATES
+      .iter()
+      .cloned()
+      .find(|state| Self::state_name(*state) == text)
+  }
+
+  fn state_name(state: git2::RepositoryState) -> &'static str {
     use git2::RepositoryState::*;
 
-    match self.state {
+    match state {
       Clean => "clean",
       Merge => "merge",
       Revert => "revert",
@@ -210,6 +695,8 @@ impl Repo {
 mod tests {
   use super::*;
 
+  use crate::testing::git;
+
   #[test]
   fn empty() -> Result<(), Error> {
     let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
@@ -222,4 +709,115 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn stashes() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let path = tempdir.path().join("repo");
+    let repo = path.to_str().unwrap();
+
+    git(&["init", repo])?;
+    git(&["-C", repo, "commit", "--allow-empty", "-m", "initial"])?;
+    fs::write(path.join("file"), "").context(error::Io { path: &path })?;
+    git(&[
+      "-C",
+      repo,
+      "stash",
+      "push",
+      "--include-untracked",
+      "-m",
+      "wip",
+    ])?;
+
+    let repo = Repo::new(&path)?;
+
+    assert_eq!(repo.stashes(), 1);
+    assert!(repo.stash().unwrap().ends_with("wip"));
+    assert!(!repo.is_dirty(false));
+    assert!(repo.is_dirty(true));
+
+    Ok(())
+  }
+
+  #[test]
+  fn pull() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let origin = tempdir.path().join("origin");
+    let path = tempdir.path().join("clone");
+    let origin = origin.to_str().unwrap();
+    let clone = path.to_str().unwrap();
+
+    git(&["init", origin])?;
+    git(&["-C", origin, "commit", "--allow-empty", "-m", "one"])?;
+    git(&["clone", origin, clone])?;
+    git(&["-C", origin, "commit", "--allow-empty", "-m", "two"])?;
+
+    let mut repo = Repo::new(&path)?;
+    assert!(matches!(repo.pull()?, Pull::UpToDate));
+
+    repo.fetch()?;
+    assert!(matches!(repo.pull()?, Pull::Updated { commits: 1 }));
+
+    git(&["-C", origin, "commit", "--allow-empty", "-m", "three"])?;
+    git(&["-C", clone, "commit", "--allow-empty", "-m", "four"])?;
+
+    repo.fetch()?;
+    assert!(matches!(repo.pull()?, Pull::Diverged { .. }));
+
+    Ok(())
+  }
+
+  #[test]
+  fn clone_git2() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let origin = tempdir.path().join("origin");
+    let path = tempdir.path().join("clone");
+    let origin = origin.to_str().unwrap();
+
+    git(&["init", origin])?;
+    git(&["-C", origin, "commit", "--allow-empty", "-m", "one"])?;
+    git(&["-C", origin, "checkout", "-b", "dev"])?;
+    git(&["-C", origin, "commit", "--allow-empty", "-m", "two"])?;
+    git(&["-C", origin, "checkout", "-"])?;
+
+    let options = serde_yaml::from_str("branch: dev").unwrap();
+
+    let repo = Repo::clone("local", origin, &path, &options, Backend::Git2)?;
+
+    assert_eq!(repo.head(), "dev");
+    assert_eq!(repo.remotes().keys().collect::<Vec<_>>(), &["local"]);
+    assert!(repo.branches().all(|branch| branch.is_synced()));
+
+    Ok(())
+  }
+
+  #[test]
+  fn run_bare() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let path = tempdir.path().join("bare.git");
+
+    git(&["init", "--bare", path.to_str().unwrap()])?;
+
+    Repo::new(&path)?.run(vec!["touch".into(), "marker".into()])?;
+
+    assert!(path.join("marker").is_file());
+    assert!(!tempdir.path().join("marker").exists());
+
+    Ok(())
+  }
+
+  #[test]
+  fn states() {
+    for state in Repo::STATES {
+      let name = Repo::state_name(*state);
+      assert_eq!(Repo::parse_state(name), Some(*state));
+      assert_eq!(Repo::parse_state(&name.replace(' ', "-")), Some(*state));
+    }
+
+    assert_eq!(Repo::parse_state("bogus"), None);
+  }
 }
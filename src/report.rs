@@ -0,0 +1,171 @@
+use crate::common::*;
+
+const VERSION: u64 = 1;
+
+#[derive(Serialize)]
+pub(crate) struct Status {
+  pub(crate) version: u64,
+  pub(crate) repositories: Vec<Repository>,
+  pub(crate) failures: Vec<Failure>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Repository {
+  pub(crate) name: String,
+  pub(crate) path: PathBuf,
+  pub(crate) head: String,
+  pub(crate) state: String,
+  pub(crate) dirty: bool,
+  pub(crate) unpushed: bool,
+  pub(crate) stashes: usize,
+  pub(crate) stash: Option<String>,
+  pub(crate) shallow: bool,
+  pub(crate) partial: bool,
+  pub(crate) files: Vec<File>,
+  pub(crate) branches: Vec<Branch>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct File {
+  pub(crate) path: String,
+  pub(crate) status: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Branch {
+  pub(crate) name: String,
+  pub(crate) upstream: Option<String>,
+  pub(crate) ahead: usize,
+  pub(crate) behind: usize,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Failure {
+  pub(crate) path: PathBuf,
+  pub(crate) error: String,
+}
+
+impl Status {
+  pub(crate) fn new(repositories: Vec<Repository>, failures: Vec<Failure>) -> Status {
+    Status {
+      version: VERSION,
+      repositories,
+      failures,
+    }
+  }
+
+  pub(crate) fn json(&self) -> Result<String, Error> {
+    serde_json::to_string_pretty(self).context(error::Json)
+  }
+
+  pub(crate) fn porcelain(&self) -> String {
+    let mut lines = vec![format!("version {}", self.version)];
+
+    for repository in &self.repositories {
+      lines.push(format!(
+        "repository {}\t{}\t{}\t{}\t{}",
+        repository.name,
+        repository.path.display(),
+        repository.head,
+        repository.state,
+        if repository.dirty { "dirty" } else { "clean" },
+      ));
+
+      if repository.shallow {
+        lines.push("shallow".to_owned());
+      }
+
+      if repository.partial {
+        lines.push("partial".to_owned());
+      }
+
+      for file in &repository.files {
+        lines.push(format!("file {}\t{}", file.status, file.path));
+      }
+
+      if let Some(stash) = &repository.stash {
+        lines.push(format!("stashes {}\t{}", repository.stashes, stash));
+      }
+
+      for branch in &repository.branches {
+        lines.push(format!(
+          "branch {}\t{}\t{}\t{}",
+          branch.name,
+          branch.upstream.as_deref().unwrap_or("-"),
+          branch.ahead,
+          branch.behind,
+        ));
+      }
+    }
+
+    for failure in &self.failures {
+      lines.push(format!(
+        "failure {}\t{}",
+        failure.path.display(),
+        failure.error.replace('\n', " ")
+      ));
+    }
+
+    lines.push(String::new());
+
+    lines.join("\n")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn porcelain() {
+    let status = Status::new(
+      vec![Repository {
+        name: "just".to_owned(),
+        path: PathBuf::from("/src/just"),
+        head: "master".to_owned(),
+        state: "cherry-pick".to_owned(),
+        dirty: true,
+        unpushed: true,
+        stashes: 2,
+        stash: Some("On master: wip".to_owned()),
+        shallow: true,
+        partial: false,
+        files: vec![File {
+          path: "README.md".to_owned(),
+          status: ".M".to_owned(),
+        }],
+        branches: vec![
+          Branch {
+            name: "master".to_owned(),
+            upstream: Some("origin/master".to_owned()),
+            ahead: 2,
+            behind: 1,
+          },
+          Branch {
+            name: "feature".to_owned(),
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+          },
+        ],
+      }],
+      vec![Failure {
+        path: PathBuf::from("/src/broken"),
+        error: "bad\nrepo".to_owned(),
+      }],
+    );
+
+    assert_eq!(
+      status.porcelain(),
+      "version 1
+repository just\t/src/just\tmaster\tcherry-pick\tdirty
+shallow
+file .M\tREADME.md
+stashes 2\tOn master: wip
+branch master\torigin/master\t2\t1
+branch feature\t-\t0\t0
+failure /src/broken\tbad repo
+"
+    );
+  }
+}
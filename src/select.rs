@@ -0,0 +1,116 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+pub(crate) struct Select {
+  #[structopt(long = "dirty", conflicts_with = "clean")]
+  dirty: bool,
+  #[structopt(long = "clean")]
+  clean: bool,
+  #[structopt(long = "branch")]
+  branch: Option<String>,
+  #[structopt(long = "name", parse(try_from_str = Select::glob))]
+  name: Vec<GlobMatcher>,
+  #[structopt(long = "state", parse(try_from_str = Select::state))]
+  state: Option<git2::RepositoryState>,
+  #[structopt(long = "exclude", parse(try_from_str = Select::glob))]
+  exclude: Vec<GlobMatcher>,
+  #[structopt(long = "group")]
+  group: Vec<String>,
+}
+
+impl Select {
+  fn state(text: &str) -> Result<git2::RepositoryState, String> {
+    Repo::parse_state(text).ok_or_else(|| format!("unknown repository state `{}`", text))
+  }
+
+  fn glob(text: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(Glob::new(text)?.compile_matcher())
+  }
+
+  pub(crate) fn groups<'a>(&self, config: &'a Config) -> Result<Vec<&'a Group>, Error> {
+    self.group.iter().map(|name| config.group(name)).collect()
+  }
+
+  pub(crate) fn matches(&self, repo: &Repo, groups: &[&Group]) -> bool {
+    if !groups.is_empty() && !groups.iter().any(|group| group.contains(repo)) {
+      return false;
+    }
+
+    if self.dirty && !repo.is_dirty(false) {
+      return false;
+    }
+
+    if self.clean && repo.is_dirty(false) {
+      return false;
+    }
+
+    if let Some(branch) = &self.branch {
+      if repo.head() != branch {
+        return false;
+      }
+    }
+
+    if let Some(state) = self.state {
+      if repo.repository_state() != state {
+        return false;
+      }
+    }
+
+    if !self.name.is_empty()
+      && !self
+        .name
+        .iter()
+        .any(|matcher| matcher.is_match(repo.name()))
+    {
+      return false;
+    }
+
+    !self
+      .exclude
+      .iter()
+      .any(|matcher| matcher.is_match(repo.name()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::testing::git;
+
+  fn select(args: &[&str]) -> Select {
+    Select::from_iter_safe(std::iter::once("select").chain(args.iter().cloned())).unwrap()
+  }
+
+  #[test]
+  fn matches() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let path = tempdir.path().join("repo");
+
+    git(&["init", path.to_str().unwrap()])?;
+    git(&["-C", path.to_str().unwrap(), "checkout", "-b", "main"])?;
+
+    let repo = Repo::new(&path)?.with_name("github/casey/just".to_owned());
+
+    assert!(select(&[]).matches(&repo, &[]));
+    assert!(select(&["--clean", "--state", "clean"]).matches(&repo, &[]));
+    assert!(select(&["--name", "github/*"]).matches(&repo, &[]));
+    assert!(!select(&["--name", "work/*"]).matches(&repo, &[]));
+    assert!(!select(&["--exclude", "*/just"]).matches(&repo, &[]));
+    assert!(!select(&["--dirty"]).matches(&repo, &[]));
+    assert!(!select(&["--branch", "other"]).matches(&repo, &[]));
+    assert!(!select(&["--state", "rebase-interactive"]).matches(&repo, &[]));
+    assert!(Select::from_iter_safe(&["select", "--state", "bogus"]).is_err());
+
+    let rust = Group::from_raw("rust", vec!["github/casey/*".to_owned()])?;
+    let work = Group::from_raw("work", vec!["work/*".to_owned(), "dotfiles".to_owned()])?;
+
+    assert!(select(&[]).matches(&repo, &[&rust]));
+    assert!(select(&[]).matches(&repo, &[&work, &rust]));
+    assert!(!select(&[]).matches(&repo, &[&work]));
+    assert_eq!(work.literals().collect::<Vec<&str>>(), &["dotfiles"]);
+
+    Ok(())
+  }
+}
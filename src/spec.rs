@@ -7,7 +7,17 @@ pub(crate) struct Spec {
 }
 
 impl Spec {
-  pub(crate) fn remote(&self) -> Result<String, Error> {
-    self.provider.remote(&self.user, &self.project)
+  pub(crate) fn remote(&self, protocol: Option<&str>) -> Result<String, Error> {
+    self.provider.remote(&self.user, &self.project, protocol)
+  }
+
+  pub(crate) fn web(&self, context: tera::Context) -> Result<String, Error> {
+    self.provider.web(&self.user, &self.project, context)
+  }
+}
+
+impl Display for Spec {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}:{}/{}", self.provider.name, self.user, self.project)
   }
 }
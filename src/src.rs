@@ -2,50 +2,159 @@ use crate::common::*;
 
 pub(crate) struct Src {
   repos: Vec<Repo>,
+  failures: Vec<(PathBuf, Error)>,
 }
 
 impl Src {
-  pub(crate) fn load(path: &Path) -> Result<Src, Error> {
-    let mut repos = Vec::new();
+  pub(crate) fn load(config: &Config) -> Result<Src, Error> {
+    let root = config.srcdir();
 
     let style = ProgressStyle::default_spinner().template("🧿  {spinner} {msg}");
 
     let spinner = ProgressBar::new_spinner();
-    spinner.set_message(&format!("Searching {} for repositories...", path.display()));
+    spinner.set_message(&format!("Searching {} for repositories...", root.display()));
+    spinner.set_style(style);
+
+    let src = Self::scan(root, config.max_depth(), config.jobs(), &spinner)?;
+
+    let style = ProgressStyle::default_spinner().template("🧿  {msg}");
     spinner.set_style(style);
 
-    for result in fs::read_dir(path).context(error::Io { path })? {
+    spinner.finish_with_message(&format!("Found {} repositories.", src.repos.len()));
+
+    Ok(src)
+  }
+
+  fn scan(root: &Path, depth: usize, jobs: usize, spinner: &ProgressBar) -> Result<Src, Error> {
+    let mut paths = Vec::new();
+    let mut failures = Vec::new();
+
+    Self::discover(root, depth, spinner, &mut paths, &mut failures)?;
+
+    paths.sort();
+
+    spinner.set_length(paths.len() as u64);
+    spinner.set_message("Inspecting repositories...");
+    spinner.set_style(ProgressStyle::default_spinner().template("🧿  {spinner} {msg} {pos}/{len}"));
+
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(jobs)
+      .build()
+      .context(error::ThreadPool)?;
+
+    let results = pool.install(|| {
+      paths
+        .par_iter()
+        .map(|path| {
+          let result = Repo::new(path);
+
+          spinner.inc(1);
+
+          result
+        })
+        .collect::<Vec<Result<Repo, Error>>>()
+    });
+
+    let mut repos = Vec::new();
+
+    for (path, result) in paths.into_iter().zip(results) {
+      match result {
+        Ok(repo) => {
+          let name = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .into_owned();
+
+          repos.push(repo.with_name(name));
+        }
+        Err(error) => failures.push((path, error)),
+      }
+    }
+
+    failures.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(Src { repos, failures })
+  }
+
+  fn discover(
+    dir: &Path,
+    depth: usize,
+    spinner: &ProgressBar,
+    paths: &mut Vec<PathBuf>,
+    failures: &mut Vec<(PathBuf, Error)>,
+  ) -> Result<(), Error> {
+    for result in fs::read_dir(dir).context(error::Io { path: dir })? {
       spinner.tick();
 
-      let entry = result.context(error::Io { path })?;
+      let entry = result.context(error::Io { path: dir })?;
+
+      let path = entry.path();
 
-      if entry.file_name() == ".DS_Store" {
+      if !path.is_dir() {
         continue;
       }
 
-      let path = entry.path();
+      if Repo::is_repo(&path) {
+        paths.push(path);
+      } else if depth > 1 {
+        if let Err(error) = Self::discover(&path, depth - 1, spinner, paths, failures) {
+          failures.push((path, error));
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn select(&mut self, select: &Select, config: &Config) -> Result<(), Error> {
+    let groups = select.groups(config)?;
+
+    self.repos.retain(|repo| select.matches(repo, &groups));
 
-      repos.push(Repo::new(&path)?);
+    Ok(())
+  }
+
+  pub(crate) fn strict(&self) -> Result<(), Error> {
+    if self.failures.is_empty() {
+      Ok(())
+    } else {
+      Err(Error::Inspect {
+        count: self.failures.len(),
+      })
     }
+  }
 
-    let style = ProgressStyle::default_spinner().template("🧿  {msg}");
-    spinner.set_style(style);
+  pub(crate) fn print_failures(&self) {
+    if self.failures.is_empty() {
+      return;
+    }
 
-    spinner.finish_with_message(&format!("Found {} repositories.", repos.len()));
+    eprintln!("could not inspect:");
 
-    Ok(Src { repos })
+    for (path, error) in &self.failures {
+      eprintln!("{}: {}", path.display(), error);
+    }
   }
 
-  pub(crate) fn is_dirty(&self) -> bool {
-    self.repos.iter().any(Repo::is_dirty)
+  pub(crate) fn is_dirty(&self, stashes: bool) -> bool {
+    self.repos.iter().any(|repo| repo.is_dirty(stashes))
   }
 
   pub(crate) fn print_status(&self) {
-    for (i, repo) in self.repos.iter().filter(|repo| repo.is_dirty()).enumerate() {
-      if i > 0 {
+    let mut first = true;
+
+    for repo in self
+      .repos
+      .iter()
+      .filter(|repo| repo.is_dirty(true) || repo.is_unpushed() || repo.is_behind())
+    {
+      if !first {
         eprintln!();
       }
 
+      first = false;
+
       eprint!("{}", repo.name());
 
       if repo.head() != "master" {
@@ -61,47 +170,1089 @@ impl Src {
       for (path, status) in repo.files() {
         eprintln!("{} {}", status, path);
       }
+
+      if let Some(stash) = repo.stash() {
+        eprintln!(
+          "{} {} (newest: {})",
+          repo.stashes(),
+          if repo.stashes() == 1 {
+            "stash"
+          } else {
+            "stashes"
+          },
+          stash
+        );
+      }
+
+      for branch in repo.branches().filter(|branch| !branch.is_synced()) {
+        eprintln!("{}", branch);
+      }
+    }
+
+    for (kind, repos) in [
+      ("shallow", self.names(Repo::is_shallow)),
+      ("partial", self.names(Repo::is_partial)),
+    ] {
+      if repos.is_empty() {
+        continue;
+      }
+
+      if !first {
+        eprintln!();
+      }
+
+      first = false;
+
+      eprintln!("{} clones: {}", kind, repos.join(", "));
+    }
+
+    if !first && !self.failures.is_empty() {
+      eprintln!();
+    }
+
+    self.print_failures();
+  }
+
+  fn names(&self, predicate: impl Fn(&Repo) -> bool) -> Vec<&str> {
+    self
+      .repos
+      .iter()
+      .filter(|repo| predicate(repo))
+      .map(Repo::name)
+      .collect()
+  }
+
+  pub(crate) fn report(&self) -> report::Status {
+    report::Status::new(
+      self.repos.iter().map(Repo::report).collect(),
+      self
+        .failures
+        .iter()
+        .map(|(path, error)| report::Failure {
+          path: path.clone(),
+          error: error.to_string(),
+        })
+        .collect(),
+    )
+  }
+
+  pub(crate) fn only(&mut self, names: &[String]) -> Result<(), Error> {
+    for name in names {
+      if !self.repos.iter().any(|repo| repo.name() == name) {
+        return Err(Error::UnknownRepository { name: name.clone() });
+      }
+    }
+
+    self
+      .repos
+      .retain(|repo| names.iter().any(|name| name == repo.name()));
+
+    Ok(())
+  }
+
+  /// Render the URL of `repo`'s remote for `provider`. The user is taken from
+  /// the repository's existing remotes, falling back to the default user when
+  /// none of them can be identified.
+  fn provider_remote(config: &Config, repo: &Repo, provider: &str) -> Result<String, Error> {
+    let remotes = repo.remotes();
+
+    let user = remotes
+      .get(provider)
+      .and_then(|url| config.remote_user(url))
+      .or_else(|| {
+        config
+          .identify_remotes(remotes)
+          .and_then(|(remote, _)| config.remote_user(&remotes[&remote]))
+      });
+
+    config
+      .resolve(Some(provider), user.as_deref(), repo.project())?
+      .remote(None)
+  }
+
+  /// Add `remote` to repositories that lack it, rendering the URL from the
+  /// provider of the same name. In a dry run nothing is added, and the URLs
+  /// that would have been added are returned by repository name.
+  pub(crate) fn create_remote(
+    &mut self,
+    config: &Config,
+    remote: &str,
+    dry_run: bool,
+  ) -> Result<BTreeMap<String, String>, Error> {
+    let mut pending = BTreeMap::new();
+
+    for repo in &mut self.repos {
+      if repo.remotes().contains_key(remote) {
+        continue;
+      }
+
+      let url = Self::provider_remote(config, repo, remote)?;
+
+      if dry_run {
+        eprintln!("Would add remote to `{}`: {} {}", repo.name(), remote, url);
+        pending.insert(repo.name().to_owned(), url);
+      } else {
+        repo.add_remote(remote, &url)?;
+        eprintln!("Added remote to `{}`: {} {}", repo.name(), remote, url);
+      }
     }
+
+    Ok(pending)
+  }
+
+  /// Ensure every repository has a remote named after each of `providers`,
+  /// pointing at the URL rendered from that provider's default template.
+  pub(crate) fn mirror(
+    &mut self,
+    config: &Config,
+    providers: &[String],
+    fix: bool,
+  ) -> Result<(), Error> {
+    let mut mismatches = 0;
+    let mut errors = Vec::new();
+
+    for repo in &mut self.repos {
+      match Self::mirror_repo(config, repo, providers, fix) {
+        Ok(count) => mismatches += count,
+        Err(error) => errors.push((repo.name().to_owned(), error)),
+      }
+    }
+
+    if !errors.is_empty() {
+      for (name, error) in &errors {
+        eprintln!("Failed to mirror `{}`: {}", name, error);
+      }
+      return Err(Error::MirrorAll {
+        count: errors.len(),
+      });
+    }
+
+    if mismatches == 0 {
+      Ok(())
+    } else {
+      Err(Error::MirrorMismatch { count: mismatches })
+    }
+  }
+
+  /// Returns the number of mismatched remotes left in place.
+  fn mirror_repo(
+    config: &Config,
+    repo: &mut Repo,
+    providers: &[String],
+    fix: bool,
+  ) -> Result<usize, Error> {
+    let mut mismatches = 0;
+
+    for provider in providers {
+      let url = Self::provider_remote(config, repo, provider)?;
+
+      match repo.remotes().get(provider).cloned() {
+        None => {
+          repo.add_remote(provider, &url)?;
+          eprintln!("Added remote to `{}`: {} {}", repo.name(), provider, url);
+        }
+        Some(existing) if existing == url => {}
+        Some(existing) => {
+          if fix {
+            repo.set_remote_url(provider, &url)?;
+            eprintln!(
+              "Updated remote in `{}`: {} {} -> {}",
+              repo.name(),
+              provider,
+              existing,
+              url
+            );
+          } else {
+            mismatches += 1;
+            eprintln!(
+              "Mismatched remote in `{}`: {} is {}, expected {}",
+              repo.name(),
+              provider,
+              existing,
+              url
+            );
+          }
+        }
+      }
+    }
+
+    Ok(mismatches)
+  }
+
+  /// Remove repositories without `remote`, returning the names of those
+  /// removed.
+  pub(crate) fn skip_missing_remote(&mut self, remote: &str) -> Vec<String> {
+    let skipped = self
+      .repos
+      .iter()
+      .filter(|repo| !repo.remotes().contains_key(remote))
+      .map(|repo| repo.name().to_owned())
+      .collect();
+
+    self
+      .repos
+      .retain(|repo| repo.remotes().contains_key(remote));
+
+    skipped
+  }
+
+  /// Remove dirty repositories, returning the names of those removed.
+  pub(crate) fn skip_dirty(&mut self, stashes: bool) -> Vec<String> {
+    let skipped = self
+      .repos
+      .iter()
+      .filter(|repo| repo.is_dirty(stashes))
+      .map(|repo| repo.name().to_owned())
+      .collect();
+
+    self.repos.retain(|repo| !repo.is_dirty(stashes));
+
+    skipped
   }
 
-  pub(crate) fn push_all(&self, remote: &str) -> Result<(), Error> {
+  /// Push every repository to `remote`, or to the URL in `pending` for
+  /// repositories whose remote would have been created in a dry run.
+  pub(crate) fn push_all(
+    &self,
+    remote: &str,
+    dry_run: bool,
+    pending: &BTreeMap<String, String>,
+  ) -> Result<(), Error> {
     let style = ProgressStyle::default_bar().template("Pushing: {wide_bar} {pos}/{len}");
 
     let bar = ProgressBar::new(self.repos.len() as u64);
     bar.set_style(style);
 
-    let errors = self
+    let results = self
       .repos
       .par_iter()
+      .map(|repo| {
+        let target = pending
+          .get(repo.name())
+          .map(String::as_str)
+          .unwrap_or(remote);
+
+        let result = repo.push(target, dry_run);
+
+        bar.inc(1);
+
+        (repo.name(), result)
+      })
+      .collect::<Vec<(&str, Result<Vec<RefUpdate>, Error>)>>();
+
+    bar.finish();
+
+    let width = results
+      .iter()
+      .map(|(name, _)| name.len())
+      .max()
+      .unwrap_or(0);
+
+    let mut failed = false;
+
+    for (name, result) in results {
+      match result {
+        Ok(updates) => {
+          let updates = updates
+            .iter()
+            .filter(|update| update.status != RefStatus::UpToDate)
+            .collect::<Vec<&RefUpdate>>();
+
+          if updates.is_empty() {
+            eprintln!("{:width$}  up to date", name, width = width);
+          }
+
+          for (i, update) in updates.iter().enumerate() {
+            failed |= update.status.is_rejected();
+            eprintln!(
+              "{:width$}  {}: {}",
+              if i == 0 { name } else { "" },
+              update.name(),
+              update.status,
+              width = width
+            );
+          }
+        }
+        Err(error) => {
+          failed = true;
+          eprintln!("{:width$}  failed: {}", name, error, width = width);
+        }
+      }
+    }
+
+    if failed {
+      Err(Error::PushAll {
+        remote: remote.to_owned(),
+      })
+    } else {
+      if dry_run {
+        eprintln!("Dry run, nothing was pushed.");
+      } else {
+        eprintln!("Successfully pushed all {} repositories!", self.repos.len());
+      }
+      Ok(())
+    }
+  }
+
+  pub(crate) fn fetch_all(&mut self) -> Result<(), Error> {
+    let style = ProgressStyle::default_bar().template("Fetching: {wide_bar} {pos}/{len}");
+
+    let bar = ProgressBar::new(self.repos.len() as u64);
+    bar.set_style(style);
+
+    let errors = self
+      .repos
+      .par_iter_mut()
       .flat_map(|repo| {
-        let result = repo.push(remote);
+        let result = repo.fetch();
 
         bar.inc(1);
 
-        result.err().map(|err| (repo.name(), err))
+        result.err().map(|err| (repo.name().to_owned(), err))
       })
-      .collect::<Vec<(&str, Error)>>();
+      .collect::<Vec<(String, Error)>>();
 
     bar.finish();
 
     if errors.is_empty() {
-      eprintln!("Successfully pushed all {} repositories!", self.repos.len());
+      eprintln!(
+        "Successfully fetched all {} repositories!",
+        self.repos.len()
+      );
       Ok(())
     } else {
       for (name, error) in errors {
-        eprintln!("Failed to push `{}`: {}", name, error);
+        eprintln!("Failed to fetch `{}`: {}", name, error);
       }
-      Err(Error::PushAll {
-        remote: remote.to_owned(),
+      Err(Error::FetchAll)
+    }
+  }
+
+  pub(crate) fn pull_all(&self) -> Result<(), Error> {
+    let style = ProgressStyle::default_bar().template("Pulling: {wide_bar} {pos}/{len}");
+
+    let bar = ProgressBar::new(self.repos.len() as u64);
+    bar.set_style(style);
+
+    let results = self
+      .repos
+      .par_iter()
+      .map(|repo| {
+        let result = repo.pull();
+
+        bar.inc(1);
+
+        (repo.name(), result)
       })
+      .collect::<Vec<(&str, Result<Pull, Error>)>>();
+
+    bar.finish();
+
+    let width = results
+      .iter()
+      .map(|(name, _)| name.len())
+      .max()
+      .unwrap_or(0);
+
+    let mut failed = false;
+
+    for (name, result) in results {
+      match result {
+        Ok(pull) => eprintln!("{:width$}  {}", name, pull, width = width),
+        Err(error) => {
+          failed = true;
+          eprintln!("{:width$}  failed: {}", name, error, width = width);
+        }
+      }
+    }
+
+    if failed {
+      Err(Error::PullAll)
+    } else {
+      Ok(())
     }
   }
 
-  pub(crate) fn all(&self, command: &[String]) -> Result<(), Error> {
+  pub(crate) fn sync(
+    &mut self,
+    config: &Config,
+    manifest: &Manifest,
+    dry_run: bool,
+  ) -> Result<(), Error> {
+    let mut errors = Vec::new();
+
+    for entry in &manifest.entries {
+      if let Err(error) = self.sync_entry(config, entry, dry_run) {
+        errors.push((entry.name.as_str(), error));
+      }
+    }
+
     for repo in &self.repos {
-      repo.run(command.iter().map(OsString::from).collect())?;
+      if !manifest
+        .entries
+        .iter()
+        .any(|entry| entry.name == repo.name())
+      {
+        eprintln!("Not in manifest: {}", repo.name());
+      }
     }
 
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      for (name, error) in &errors {
+        eprintln!("Failed to sync `{}`: {}", name, error);
+      }
+      Err(Error::SyncAll {
+        count: errors.len(),
+      })
+    }
+  }
+
+  fn sync_entry(
+    &mut self,
+    config: &Config,
+    entry: &manifest::Entry,
+    dry_run: bool,
+  ) -> Result<(), Error> {
+    let remotes = entry.remotes();
+
+    let repo = match self.repos.iter_mut().find(|repo| repo.name() == entry.name) {
+      Some(repo) => repo,
+      None => {
+        let destination = config.srcdir().join(&entry.name);
+
+        if dry_run {
+          eprintln!("Not on disk: {}", entry.name);
+          return Ok(());
+        }
+
+        if destination.exists() {
+          return Err(Error::DestinationExists { destination });
+        }
+
+        let options = config
+          .identify(&entry.url)
+          .map(|spec| spec.provider.clone_options().clone())
+          .unwrap_or_default();
+
+        let repo = Repo::clone(
+          &entry.origin,
+          &entry.url,
+          &destination,
+          &options,
+          config.clone_backend(),
+        )?;
+
+        self.repos.push(repo.with_name(entry.name.clone()));
+
+        self.repos.last_mut().unwrap()
+      }
+    };
+
+    for (name, url) in &remotes {
+      if repo.remotes().contains_key(name) {
+        continue;
+      }
+
+      if dry_run {
+        eprintln!("Missing remote in `{}`: {} {}", entry.name, name, url);
+      } else {
+        repo.add_remote(name, url)?;
+        eprintln!("Added remote to `{}`: {} {}", entry.name, name, url);
+      }
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn export(&self, config: &Config) -> raw::Manifest {
+    let mut repositories = Vec::new();
+
+    for repo in &self.repos {
+      let mut remotes = repo.remotes().clone();
+
+      let entry = match config.identify_remotes(&remotes) {
+        Some((remote, spec)) => {
+          let user = remotes
+            .remove(&remote)
+            .and_then(|url| config.remote_user(&url));
+
+          raw::Entry {
+            name: Some(repo.name().to_owned()).filter(|name| *name != spec.project),
+            origin: Some(remote).filter(|remote| *remote != spec.provider.name),
+            provider: Some(spec.provider.name.clone()),
+            user,
+            project: Some(spec.project),
+            url: None,
+            remotes,
+          }
+        }
+        None => {
+          let remote = if remotes.contains_key(manifest::ORIGIN) {
+            Some(manifest::ORIGIN.to_owned())
+          } else {
+            remotes.keys().next().cloned()
+          };
+
+          let url = remote
+            .as_ref()
+            .and_then(|remote| remotes.remove(remote).map(|url| (remote.clone(), url)));
+
+          match url {
+            Some((remote, url)) => raw::Entry {
+              name: Some(repo.name().to_owned())
+                .filter(|name| name != Address::parse(&url).project()),
+              origin: Some(remote).filter(|remote| remote != manifest::ORIGIN),
+              provider: None,
+              user: None,
+              project: None,
+              url: Some(url),
+              remotes,
+            },
+            None => {
+              eprintln!("Skipping `{}`: no remotes", repo.name());
+              continue;
+            }
+          }
+        }
+      };
+
+      repositories.push(entry);
+    }
+
+    raw::Manifest { repositories }
+  }
+
+  pub(crate) fn all(&self, command: &[String], keep_going: bool) -> Result<(), Error> {
+    let mut errors = Vec::new();
+
+    for repo in &self.repos {
+      if let Err(error) = repo.run(command.iter().map(OsString::from).collect()) {
+        if !keep_going {
+          return Err(Error::Run {
+            name: repo.name().to_owned(),
+            source: Box::new(error),
+          });
+        }
+
+        errors.push((repo.name(), error));
+      }
+    }
+
+    Self::all_result(errors)
+  }
+
+  pub(crate) fn all_parallel(
+    &self,
+    command: &[String],
+    jobs: usize,
+    prefix: bool,
+    keep_going: bool,
+  ) -> Result<(), Error> {
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(jobs)
+      .build()
+      .context(error::ThreadPool)?;
+
+    // Without `keep_going`, repositories not yet started are skipped once a
+    // command fails. Commands already running are allowed to finish.
+    let stop = AtomicBool::new(false);
+
+    let errors = pool.install(|| {
+      self
+        .repos
+        .par_iter()
+        .flat_map(|repo| {
+          if stop.load(Ordering::SeqCst) {
+            return None;
+          }
+
+          let command = command.iter().map(OsString::from).collect();
+
+          let result = if prefix {
+            repo.run_prefixed(command)
+          } else {
+            repo.run_buffered(command)
+          };
+
+          let error = result.err()?;
+
+          if !keep_going {
+            stop.store(true, Ordering::SeqCst);
+          }
+
+          Some((repo.name(), error))
+        })
+        .collect::<Vec<(&str, Error)>>()
+    });
+
+    if keep_going {
+      return Self::all_result(errors);
+    }
+
+    let mut errors = errors.into_iter();
+
+    match errors.next() {
+      Some((name, error)) => {
+        for (name, error) in errors {
+          eprintln!("Failed to run command in `{}`: {}", name, error);
+        }
+        Err(Error::Run {
+          name: name.to_owned(),
+          source: Box::new(error),
+        })
+      }
+      None => Ok(()),
+    }
+  }
+
+  fn all_result(errors: Vec<(&str, Error)>) -> Result<(), Error> {
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      for (name, error) in &errors {
+        eprintln!("Failed to run command in `{}`: {}", name, error);
+      }
+      Err(Error::RunAll {
+        count: errors.len(),
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::testing::{config, git};
+
+  fn init(path: &Path) -> Result<(), Error> {
+    Repo::command_output(vec!["git".into(), "init".into(), path.into()])?;
+    Ok(())
+  }
+
+  #[test]
+  fn discover_nested() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    init(&root.join("top"))?;
+    init(&root.join("github/casey/just"))?;
+    init(&root.join("top/vendor/inner"))?;
+
+    let src = Src::scan(root, 3, 2, &ProgressBar::hidden())?;
+
+    let names = src.repos.iter().map(Repo::name).collect::<Vec<&str>>();
+
+    assert_eq!(names, &["github/casey/just", "top"]);
+
+    Ok(())
+  }
+
+  #[test]
+  fn discover_failures() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    init(&root.join("good"))?;
+    fs::create_dir(root.join("plain")).context(error::Io { path: root })?;
+    fs::write(root.join("file"), "").context(error::Io { path: root })?;
+    fs::create_dir_all(root.join("broken/.git")).context(error::Io { path: root })?;
+
+    let src = Src::scan(root, 1, 2, &ProgressBar::hidden())?;
+
+    assert_eq!(src.repos.len(), 1);
+    assert_eq!(src.repos[0].name(), "good");
+    assert_eq!(src.failures.len(), 1);
+    assert_eq!(src.failures[0].0, root.join("broken"));
+    assert!(src.strict().is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn all_parallel_keep_going() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    init(&root.join("a"))?;
+    init(&root.join("b"))?;
+
+    let src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    let command = &["false".to_owned()];
+
+    match src.all_parallel(command, 1, true, true) {
+      Err(Error::RunAll { count: 2 }) => {}
+      _ => panic!("expected both repositories to fail"),
+    }
+
+    match src.all_parallel(command, 1, true, false) {
+      Err(Error::Run { name, source }) => {
+        assert_eq!(name, "a");
+        assert!(matches!(*source, Error::CommandStatus { .. }));
+      }
+      _ => panic!("expected the first failure to be returned"),
+    }
+
+    match src.all(command, false) {
+      Err(Error::Run { name, .. }) => assert_eq!(name, "a"),
+      _ => panic!("expected the first failure to be returned"),
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn export() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    let tool = root.join("tool");
+    let other = root.join("other");
+    let tool = tool.to_str().unwrap();
+    let other = other.to_str().unwrap();
+
+    init(Path::new(tool))?;
+    git(&[
+      "-C",
+      tool,
+      "remote",
+      "add",
+      "origin",
+      "https://github.com/casey/tool.git",
+    ])?;
+    git(&[
+      "-C",
+      tool,
+      "remote",
+      "add",
+      "backup",
+      "git@example.com:casey/tool.git",
+    ])?;
+
+    init(Path::new(other))?;
+    git(&["-C", other, "remote", "add", "upstream", "/srv/other.git"])?;
+
+    let src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    let manifest = src.export(&config(Config::default()));
+
+    let other = &manifest.repositories[0];
+    assert_eq!(other.url.as_deref(), Some("/srv/other.git"));
+    assert_eq!(other.origin.as_deref(), Some("upstream"));
+    assert!(other.remotes.is_empty());
+
+    let tool = &manifest.repositories[1];
+    assert_eq!(tool.provider.as_deref(), Some("github"));
+    assert_eq!(tool.user.as_deref(), Some("casey"));
+    assert_eq!(tool.project.as_deref(), Some("tool"));
+    assert_eq!(tool.origin.as_deref(), Some("origin"));
+    assert_eq!(
+      tool
+        .remotes
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<&str>>(),
+      &["backup"]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn export_userless() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    for name in &["a", "b"] {
+      let path = root.join(name);
+      init(&path)?;
+      git(&[
+        "-C",
+        path.to_str().unwrap(),
+        "remote",
+        "add",
+        "backup",
+        &format!("/srv/{}.git", name),
+      ])?;
+    }
+
+    git(&[
+      "-C",
+      root.join("b").to_str().unwrap(),
+      "remote",
+      "add",
+      "origin",
+      "git@github.com:casey/b.git",
+    ])?;
+
+    let src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    let manifest = src.export(&config(USERLESS));
+
+    let a = &manifest.repositories[0];
+    assert_eq!(a.provider.as_deref(), Some("backup"));
+    assert_eq!(a.user, None);
+    assert_eq!(a.project.as_deref(), Some("a"));
+
+    let b = &manifest.repositories[1];
+    assert_eq!(b.provider.as_deref(), Some("github"));
+    assert_eq!(b.user.as_deref(), Some("casey"));
+    assert_eq!(b.origin.as_deref(), Some("origin"));
+    assert_eq!(
+      b.remotes.get("backup").map(String::as_str),
+      Some("/srv/b.git")
+    );
+
+    Ok(())
+  }
+
+  const USERLESS: &str = "
+default-provider: github
+srcdir: /
+tmpdir: /
+providers:
+  github:
+    remote-template: git@github.com:{{user}}/{{project}}.git
+  backup:
+    remote-template: /srv/{{project}}.git
+";
+
+  const PROVIDERS: &str = "
+default-provider: github
+srcdir: /
+tmpdir: /
+providers:
+  github:
+    remote-template: git@github.com:{{user}}/{{project}}.git
+  backup:
+    remote-template: git@backup.example.com:{{user}}/{{project}}.git
+";
+
+  #[test]
+  fn missing_remote() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    let a = root.join("a");
+    let b = root.join("b");
+    let a = a.to_str().unwrap();
+    let b = b.to_str().unwrap();
+
+    init(Path::new(a))?;
+    git(&[
+      "-C",
+      a,
+      "remote",
+      "add",
+      "backup",
+      "git@backup.example.com:me/a.git",
+    ])?;
+
+    init(Path::new(b))?;
+    git(&[
+      "-C",
+      b,
+      "remote",
+      "add",
+      "origin",
+      "git@github.com:casey/b.git",
+    ])?;
+
+    let mut skipped = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+    assert_eq!(skipped.skip_missing_remote("backup"), &["b"]);
+    assert_eq!(skipped.repos.len(), 1);
+
+    let config = config(PROVIDERS);
+
+    let mut created = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    let pending = created.create_remote(&config, "backup", true)?;
+    assert_eq!(
+      pending.get("b").map(String::as_str),
+      Some("git@backup.example.com:casey/b.git")
+    );
+    assert!(!created.repos[1].remotes().contains_key("backup"));
+
+    assert!(created.create_remote(&config, "backup", false)?.is_empty());
+    assert_eq!(
+      Repo::new(Path::new(b))?
+        .remotes()
+        .get("backup")
+        .map(String::as_str),
+      Some("git@backup.example.com:casey/b.git")
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn create_remote_userless() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    let a = root.join("a");
+    let b = root.join("b");
+    let a = a.to_str().unwrap();
+    let b = b.to_str().unwrap();
+
+    init(Path::new(a))?;
+    git(&[
+      "-C",
+      a,
+      "remote",
+      "add",
+      "origin",
+      "git@github.com:casey/a.git",
+    ])?;
+    git(&["-C", a, "remote", "add", "backup", "/srv/a.git"])?;
+
+    init(Path::new(b))?;
+    git(&["-C", b, "remote", "add", "backup", "/srv/b.git"])?;
+
+    let mut src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    let pending = src.create_remote(&config(USERLESS), "github", true)?;
+    assert_eq!(
+      pending.get("a").map(String::as_str),
+      Some("git@github.com:casey/a.git")
+    );
+    assert_eq!(
+      pending.get("b").map(String::as_str),
+      Some("git@github.com:me/b.git")
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn mirror() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    let a = root.join("a");
+    let b = root.join("b");
+    let a = a.to_str().unwrap();
+    let b = b.to_str().unwrap();
+
+    init(Path::new(a))?;
+    git(&[
+      "-C",
+      a,
+      "remote",
+      "add",
+      "origin",
+      "git@github.com:casey/a.git",
+    ])?;
+
+    init(Path::new(b))?;
+    git(&[
+      "-C",
+      b,
+      "remote",
+      "add",
+      "github",
+      "git@github.com:casey/b.git",
+    ])?;
+    git(&[
+      "-C",
+      b,
+      "remote",
+      "add",
+      "backup",
+      "git@backup.example.com:casey/old.git",
+    ])?;
+
+    let config = config(PROVIDERS);
+    let providers = &["github".to_owned(), "backup".to_owned()];
+
+    let remote = |path: &str, name: &str| -> Result<Option<String>, Error> {
+      Ok(Repo::new(Path::new(path))?.remotes().get(name).cloned())
+    };
+
+    let mut src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    match src.mirror(&config, providers, false) {
+      Err(Error::MirrorMismatch { count: 1 }) => {}
+      _ => panic!("expected one mismatched remote"),
+    }
+
+    assert_eq!(
+      remote(a, "github")?.as_deref(),
+      Some("git@github.com:casey/a.git")
+    );
+    assert_eq!(
+      remote(a, "backup")?.as_deref(),
+      Some("git@backup.example.com:casey/a.git")
+    );
+    assert_eq!(
+      remote(b, "backup")?.as_deref(),
+      Some("git@backup.example.com:casey/old.git")
+    );
+
+    let mut src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    src.mirror(&config, providers, true)?;
+
+    assert_eq!(
+      remote(b, "backup")?.as_deref(),
+      Some("git@backup.example.com:casey/b.git")
+    );
+
+    match src.mirror(&config, &["unknown".to_owned()], false) {
+      Err(Error::MirrorAll { count: 2 }) => {}
+      _ => panic!("expected both repositories to fail"),
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn mirror_userless() -> Result<(), Error> {
+    let tempdir = tempfile::tempdir().context(error::Io { path: "<TEMPDIR>" })?;
+
+    let root = tempdir.path();
+
+    let a = root.join("a");
+    let a = a.to_str().unwrap();
+
+    init(Path::new(a))?;
+    git(&[
+      "-C",
+      a,
+      "remote",
+      "add",
+      "origin",
+      "git@github.com:casey/a.git",
+    ])?;
+    git(&["-C", a, "remote", "add", "backup", "/srv/a.git"])?;
+
+    let mut src = Src::scan(root, 1, 1, &ProgressBar::hidden())?;
+
+    src.mirror(
+      &config(USERLESS),
+      &["github".to_owned(), "backup".to_owned()],
+      false,
+    )?;
+
+    assert_eq!(
+      Repo::new(Path::new(a))?
+        .remotes()
+        .get("github")
+        .map(String::as_str),
+      Some("git@github.com:casey/a.git")
+    );
+
     Ok(())
   }
 }
@@ -11,42 +11,66 @@ impl Status {
   pub(crate) fn new(status: git2::Status) -> Status {
     Status { status }
   }
+
+  pub(crate) fn is_conflicted(&self) -> bool {
+    self.status.is_conflicted()
+  }
+
+  pub(crate) fn index(&self) -> char {
+    if self.status.is_index_new() {
+      'A'
+    } else if self.status.is_index_modified() {
+      'M'
+    } else if self.status.is_index_renamed() {
+      'R'
+    } else if self.status.is_index_typechange() {
+      'T'
+    } else if self.status.is_index_deleted() {
+      'D'
+    } else {
+      ' '
+    }
+  }
+
+  pub(crate) fn worktree(&self) -> char {
+    if self.status.is_wt_new() {
+      'A'
+    } else if self.status.is_wt_modified() {
+      'M'
+    } else if self.status.is_wt_renamed() {
+      'R'
+    } else if self.status.is_wt_typechange() {
+      'T'
+    } else if self.status.is_wt_deleted() {
+      'D'
+    } else {
+      ' '
+    }
+  }
+
+  pub(crate) fn code(&self) -> String {
+    if self.is_conflicted() {
+      "XX".to_owned()
+    } else {
+      [self.index(), self.worktree()]
+        .iter()
+        .map(|c| if *c == ' ' { '.' } else { *c })
+        .collect()
+    }
+  }
 }
 
 impl Display for Status {
   fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-    if self.status.is_conflicted() {
+    if self.is_conflicted() {
       write!(f, "{}", Red.paint("XX"))
     } else {
-      let index = if self.status.is_index_new() {
-        "A"
-      } else if self.status.is_index_modified() {
-        "M"
-      } else if self.status.is_index_renamed() {
-        "R"
-      } else if self.status.is_index_typechange() {
-        "T"
-      } else if self.status.is_index_deleted() {
-        "D"
-      } else {
-        " "
-      };
-
-      let worktree = if self.status.is_wt_new() {
-        "A"
-      } else if self.status.is_wt_modified() {
-        "M"
-      } else if self.status.is_wt_renamed() {
-        "R"
-      } else if self.status.is_wt_typechange() {
-        "T"
-      } else if self.status.is_wt_deleted() {
-        "D"
-      } else {
-        " "
-      };
-
-      write!(f, "{}{}", Green.paint(index), Red.paint(worktree))
+      write!(
+        f,
+        "{}{}",
+        Green.paint(self.index().to_string()),
+        Red.paint(self.worktree().to_string())
+      )
     }
   }
 }
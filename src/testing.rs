@@ -0,0 +1,18 @@
+use crate::common::*;
+
+pub(crate) fn git(args: &[&str]) -> Result<(), Error> {
+  let mut command: Vec<OsString> = vec![
+    "git".into(),
+    "-c".into(),
+    "user.name=test".into(),
+    "-c".into(),
+    "user.email=test@example.com".into(),
+  ];
+  command.extend(args.iter().map(OsString::from));
+  Repo::command_output(command)?;
+  Ok(())
+}
+
+pub(crate) fn config(yaml: &str) -> Config {
+  Config::from_raw(serde_yaml::from_str(&format!("default-user: me\n{}", yaml)).unwrap()).unwrap()
+}